@@ -2,15 +2,15 @@
 use crate::{
     args::utils::genesis_value_parser,
     dirs::{DataDirPath, MaybePlatformPath},
-    utils::DbTool,
+    utils::{parse_path, table_dispatch, DbTool},
 };
 use clap::{Parser, Subcommand};
 use comfy_table::{Cell, Row, Table as ComfyTable};
 use eyre::WrapErr;
 use human_bytes::human_bytes;
-use reth_db::{database::Database, tables};
+use reth_db::{database::Database, table::Table, tables};
 use reth_primitives::ChainSpec;
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 use tracing::error;
 
 mod get;
@@ -63,6 +63,10 @@ pub enum Subcommands {
     List(ListArgs),
     /// Gets the content of a table for the given key
     Get(get::Command),
+    /// Prints the most recent canonical blocks as JSON
+    Recent(RecentArgs),
+    /// Compares a table against the same table of another database
+    Diff(DiffArgs),
     /// Deletes all database entries
     Drop,
 }
@@ -86,6 +90,24 @@ pub struct ListArgs {
     json: bool,
 }
 
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db recent` command
+pub struct RecentArgs {
+    /// How many blocks to print, starting at the canonical tip
+    #[arg(long, short, default_value = DEFAULT_NUM_ITEMS)]
+    len: usize,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db diff` command
+pub struct DiffArgs {
+    /// The table name
+    table: String,
+    /// The path to the database to compare against
+    #[arg(long, value_name = "PATH", value_parser = parse_path)]
+    other: PathBuf,
+}
+
 impl Command {
     /// Execute `db` command
     pub async fn execute(self) -> eyre::Result<()> {
@@ -154,75 +176,64 @@ impl Command {
                 println!("{stats_table}");
             }
             Subcommands::List(args) => {
-                macro_rules! table_tui {
-                    ($arg:expr, $start:expr, $len:expr => [$($table:ident),*]) => {
-                        match $arg {
-                            $(stringify!($table) => {
-                                tool.db.view(|tx| {
-                                    let table_db = tx.inner.open_db(Some(stringify!($table))).wrap_err("Could not open db.")?;
-                                    let stats = tx.inner.db_stat(&table_db).wrap_err(format!("Could not find table: {}", stringify!($table)))?;
-                                    let total_entries = stats.entries();
-                                    if $start > total_entries - 1 {
-                                        error!(
-                                            target: "reth::cli",
-                                            "Start index {start} is greater than the final entry index ({final_entry_idx}) in the table {table}",
-                                            start = $start,
-                                            final_entry_idx = total_entries - 1,
-                                            table = stringify!($table)
-                                        );
-                                        return Ok(());
-                                    }
-
-                                    if args.json {
-                                        let list_result = tool.list::<tables::$table>(args.skip, args.len,args.reverse)?.into_iter().collect::<Vec<_>>();
-                                        println!("{}", serde_json::to_string_pretty(&list_result)?);
-                                        Ok(())
-                                    } else {
-                                        tui::DbListTUI::<_, tables::$table>::new(|skip, count| {
-                                            tool.list::<tables::$table>(skip, count, args.reverse).unwrap()
-                                        }, $start, $len, total_entries).run()
-                                    }
-                                })??
-                            },)*
-                            _ => {
-                                error!(target: "reth::cli", "Unknown table.");
-                                return Ok(());
-                            }
+                table_dispatch!(args.table.as_str(), T => {
+                    tool.db.view(|tx| {
+                        let table_db = tx.inner.open_db(Some(T::NAME)).wrap_err("Could not open db.")?;
+                        let stats = tx.inner.db_stat(&table_db).wrap_err(format!("Could not find table: {}", T::NAME))?;
+                        let total_entries = stats.entries();
+                        if args.skip > total_entries - 1 {
+                            error!(
+                                target: "reth::cli",
+                                "Start index {start} is greater than the final entry index ({final_entry_idx}) in the table {table}",
+                                start = args.skip,
+                                final_entry_idx = total_entries - 1,
+                                table = T::NAME
+                            );
+                            return Ok(());
                         }
-                    }
-                }
-
-                table_tui!(args.table.as_str(), args.skip, args.len => [
-                    CanonicalHeaders,
-                    HeaderTD,
-                    HeaderNumbers,
-                    Headers,
-                    BlockBodyIndices,
-                    BlockOmmers,
-                    BlockWithdrawals,
-                    TransactionBlock,
-                    Transactions,
-                    TxHashNumber,
-                    Receipts,
-                    PlainStorageState,
-                    PlainAccountState,
-                    Bytecodes,
-                    AccountHistory,
-                    StorageHistory,
-                    AccountChangeSet,
-                    StorageChangeSet,
-                    HashedAccount,
-                    HashedStorage,
-                    AccountsTrie,
-                    StoragesTrie,
-                    TxSenders,
-                    SyncStage,
-                    SyncStageProgress
-                ]);
+
+                        if args.json {
+                            let list_result = tool.list::<T>(args.skip, args.len, args.reverse)?.into_iter().collect::<Vec<_>>();
+                            println!("{}", serde_json::to_string_pretty(&list_result)?);
+                            Ok(())
+                        } else {
+                            tui::DbListTUI::<_, T>::new(|skip, count| {
+                                tool.list::<T>(skip, count, args.reverse).unwrap()
+                            }, args.skip, args.len, total_entries).run()
+                        }
+                    })??
+                }, _ => {
+                    error!(target: "reth::cli", "Unknown table.");
+                    return Ok(());
+                });
             }
             Subcommands::Get(command) => {
                 command.execute(tool)?;
             }
+            Subcommands::Recent(args) => {
+                let blocks = tool.recent_blocks(args.len)?;
+                println!("{}", serde_json::to_string_pretty(&blocks)?);
+            }
+            Subcommands::Diff(args) => {
+                let other_db = reth_db::mdbx::Env::<reth_db::mdbx::WriteMap>::open(
+                    args.other.as_ref(),
+                    reth_db::mdbx::EnvKind::RO,
+                )?;
+                let other = DbTool::new(&other_db, self.chain.clone())?;
+
+                table_dispatch!(args.table.as_str(), T => {
+                    let diff = tool.diff_table::<T, _>(&other)?;
+                    let diff = serde_json::json!({
+                        "only_in_self": diff.only_in_self,
+                        "only_in_other": diff.only_in_other,
+                        "changed": diff.changed,
+                    });
+                    println!("{}", serde_json::to_string_pretty(&diff)?);
+                }, _ => {
+                    error!(target: "reth::cli", "Unknown table.");
+                    return Ok(());
+                });
+            }
             Subcommands::Drop => {
                 tool.drop(db_path)?;
             }
@@ -242,4 +253,13 @@ mod tests {
         let cmd = Command::try_parse_from(["reth", "stats", "--datadir", "../mainnet"]).unwrap();
         assert_eq!(cmd.datadir.as_ref(), Some(Path::new("../mainnet")));
     }
+
+    #[test]
+    fn parse_diff_args() {
+        let cmd = Command::try_parse_from(["reth", "diff", "Headers", "--other", "../reference"])
+            .unwrap();
+        let Subcommands::Diff(args) = cmd.command else { panic!("expected diff subcommand") };
+        assert_eq!(args.table, "Headers");
+        assert_eq!(args.other, Path::new("../reference"));
+    }
 }
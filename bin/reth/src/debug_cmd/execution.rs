@@ -4,7 +4,7 @@ use crate::{
     dirs::{DataDirPath, MaybePlatformPath},
     node::events,
     runner::CliContext,
-    utils::get_single_header,
+    utils::get_single_header_validated,
 };
 use clap::Parser;
 use futures::{stream::select as stream_select, StreamExt};
@@ -185,7 +185,13 @@ impl Command {
     ) -> eyre::Result<H256> {
         info!(target: "reth::cli", ?block, "Fetching block from the network.");
         loop {
-            match get_single_header(&client, BlockHashOrNumber::Number(block)).await {
+            match get_single_header_validated(
+                &client,
+                BlockHashOrNumber::Number(block),
+                &self.chain,
+            )
+            .await
+            {
                 Ok(tip_header) => {
                     info!(target: "reth::cli", ?block, "Successfully fetched block");
                     return Ok(tip_header.hash)
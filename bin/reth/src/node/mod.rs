@@ -6,7 +6,7 @@ use crate::{
     dirs::DataDirPath,
     prometheus_exporter,
     runner::CliContext,
-    utils::get_single_header,
+    utils::get_single_header_validated,
     version::SHORT_VERSION,
 };
 use clap::Parser;
@@ -581,7 +581,7 @@ impl Command {
 
         info!(target: "reth::cli", ?tip, "Fetching tip block from the network.");
         loop {
-            match get_single_header(&client, tip).await {
+            match get_single_header_validated(&client, tip, &self.chain).await {
                 Ok(tip_header) => {
                     info!(target: "reth::cli", ?tip, "Successfully fetched tip");
                     return Ok(tip_header)
@@ -6,7 +6,7 @@ use crate::{
         DiscoveryArgs,
     },
     dirs::{DataDirPath, MaybePlatformPath},
-    utils::get_single_header,
+    utils::get_single_header_validated,
 };
 use backon::{ConstantBuilder, Retryable};
 use clap::{Parser, Subcommand};
@@ -139,10 +139,15 @@ impl Command {
 
         match self.command {
             Subcommands::Header { id } => {
-                let header = (move || get_single_header(fetch_client.clone(), id))
-                    .retry(&backoff)
-                    .notify(|err, _| println!("Error requesting header: {err}. Retrying..."))
-                    .await?;
+                let chain = self.chain.clone();
+                let header = (move || {
+                    let client = fetch_client.clone();
+                    let chain = chain.clone();
+                    async move { get_single_header_validated(client, id, &chain).await }
+                })
+                .retry(&backoff)
+                .notify(|err, _| println!("Error requesting header: {err}. Retrying..."))
+                .await?;
                 println!("Successfully downloaded header: {header:?}");
             }
             Subcommands::Body { id } => {
@@ -151,8 +156,18 @@ impl Command {
                     BlockHashOrNumber::Number(number) => {
                         println!("Block number provided. Downloading header first...");
                         let client = fetch_client.clone();
+                        let chain = self.chain.clone();
                         let header = (move || {
-                            get_single_header(client.clone(), BlockHashOrNumber::Number(number))
+                            let client = client.clone();
+                            let chain = chain.clone();
+                            async move {
+                                get_single_header_validated(
+                                    client,
+                                    BlockHashOrNumber::Number(number),
+                                    &chain,
+                                )
+                                .await
+                            }
                         })
                         .retry(&backoff)
                         .notify(|err, _| println!("Error requesting header: {err}. Retrying..."))
@@ -1,21 +1,41 @@
 //! Common CLI utility functions.
 
-use eyre::{Result, WrapErr};
+use eyre::Result;
+use futures::{stream, StreamExt, TryStreamExt};
 use reth_db::{
-    cursor::DbCursorRO,
-    database::Database,
-    table::Table,
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
+    database::{Database, DatabaseGAT},
+    table::{Compress, DupSort, Table},
+    tables,
     transaction::{DbTx, DbTxMut},
+    DatabaseError, RawTable,
 };
-use reth_interfaces::p2p::{
-    headers::client::{HeadersClient, HeadersRequest},
-    priority::Priority,
+use reth_interfaces::{
+    consensus::ConsensusError,
+    executor::BlockExecutionError,
+    p2p::{
+        error::{DownloadError, RequestError},
+        headers::client::{HeadersClient, HeadersRequest},
+        priority::Priority,
+    },
 };
-use reth_primitives::{BlockHashOrNumber, ChainSpec, HeadersDirection, SealedHeader};
+use reth_primitives::{
+    BlockHashOrNumber, BlockNumber, ChainSpec, Hardfork, HeadersDirection, PeerId, Receipt,
+    SealedBlock, SealedHeader, H256,
+};
+use reth_provider::{
+    BlockExecutor, BlockNumProvider, BlockProvider, ExecutorFactory, HeaderProvider,
+    ProviderFactory, ReceiptProvider,
+};
+use serde::Serialize;
 use std::{
+    cmp::Ordering,
     env::VarError,
+    io::Write,
+    ops::{Bound, RangeBounds, RangeInclusive},
     path::{Path, PathBuf},
     sync::Arc,
+    time::Instant,
 };
 use tracing::info;
 
@@ -27,35 +47,349 @@ pub async fn get_single_header<Client>(
 where
     Client: HeadersClient,
 {
-    let request = HeadersRequest { direction: HeadersDirection::Rising, limit: 1, start: id };
+    get_single_header_in_direction(client, id, HeadersDirection::Rising).await
+}
+
+/// Same as [`get_single_header`], but the header must also be consistent with the given chain
+/// spec, see [`validate_header_against_chain_spec`].
+pub async fn get_single_header_validated<Client>(
+    client: Client,
+    id: BlockHashOrNumber,
+    chain: &ChainSpec,
+) -> Result<SealedHeader>
+where
+    Client: HeadersClient,
+{
+    request_single_header(client, id, HeadersDirection::Rising, Some(chain), None, |_| {}).await
+}
+
+/// Same as [`get_single_header`], but fails with [`RequestError::Timeout`] if the header did not
+/// arrive by the given deadline, regardless of the timeouts of the client.
+///
+/// The in-flight request is dropped once the deadline passes.
+pub async fn get_single_header_with_deadline<Client>(
+    client: Client,
+    id: BlockHashOrNumber,
+    deadline: Instant,
+) -> Result<SealedHeader>
+where
+    Client: HeadersClient,
+{
+    tokio::time::timeout_at(deadline.into(), get_single_header(client, id))
+        .await
+        .map_err(|_| DownloadError::RequestError(RequestError::Timeout))?
+}
+
+/// Same as [`get_single_header`], but additionally invokes `on_bad_peer` with the id of the peer
+/// whenever it is reported for a bad response.
+///
+/// [`DownloadClient::report_bad_message`][reth_interfaces::p2p::download::DownloadClient] may
+/// only soft-penalize the peer, so this allows callers to drop or blacklist it permanently.
+pub async fn get_single_header_with_bad_peer_hook<Client>(
+    client: Client,
+    id: BlockHashOrNumber,
+    on_bad_peer: impl FnMut(PeerId),
+) -> Result<SealedHeader>
+where
+    Client: HeadersClient,
+{
+    request_single_header(client, id, HeadersDirection::Rising, None, None, on_bad_peer).await
+}
+
+/// Get the header of the given block number from network and check that its hash matches the
+/// expected hash, e.g. of a hardcoded checkpoint.
+///
+/// The peer is reported as bad if it returns a header with a different hash, which guards against
+/// being fed a forged chain by eclipsing peers while bootstrapping.
+pub async fn get_single_header_checked<Client>(
+    client: Client,
+    number: BlockNumber,
+    expected_hash: H256,
+) -> Result<SealedHeader>
+where
+    Client: HeadersClient,
+{
+    request_single_header(
+        client,
+        number.into(),
+        HeadersDirection::Rising,
+        None,
+        Some(expected_hash),
+        |_| {},
+    )
+    .await
+}
+
+/// Get a single header from network, requested in the given direction.
+///
+/// The direction determines how the peer walks from the requested block, e.g.
+/// [`HeadersDirection::Falling`] when walking backward from a known tip.
+pub async fn get_single_header_in_direction<Client>(
+    client: Client,
+    id: BlockHashOrNumber,
+    direction: HeadersDirection,
+) -> Result<SealedHeader>
+where
+    Client: HeadersClient,
+{
+    request_single_header(client, id, direction, None, None, |_| {}).await
+}
+
+/// Requests a single header in the given direction and validates the response, invoking
+/// `on_bad_peer` whenever the peer is reported for a bad response.
+///
+/// If `expected_hash` is set, the header must also have that hash.
+async fn request_single_header<Client>(
+    client: Client,
+    id: BlockHashOrNumber,
+    direction: HeadersDirection,
+    chain: Option<&ChainSpec>,
+    expected_hash: Option<H256>,
+    mut on_bad_peer: impl FnMut(PeerId),
+) -> Result<SealedHeader>
+where
+    Client: HeadersClient,
+{
+    let request = HeadersRequest { direction, limit: 1, start: id };
 
     let (peer_id, response) =
         client.get_headers_with_priority(request, Priority::High).await?.split();
 
     if response.len() != 1 {
         client.report_bad_message(peer_id);
-        eyre::bail!("Invalid number of headers received. Expected: 1. Received: {}", response.len())
+        on_bad_peer(peer_id);
+        let error = if response.is_empty() {
+            DownloadError::EmptyResponse
+        } else {
+            DownloadError::TooManyHeaders { received: response.len(), expected: 1 }
+        };
+        return Err(eyre::Report::new(error.with_peer(peer_id)).wrap_err(format!(
+            "Invalid number of headers received. Expected: 1. Received: {}",
+            response.len()
+        )))
     }
 
     let header = response.into_iter().next().unwrap().seal_slow();
 
-    let valid = match id {
-        BlockHashOrNumber::Hash(hash) => header.hash() == hash,
-        BlockHashOrNumber::Number(number) => header.number == number,
-    };
+    let error = match id {
+        BlockHashOrNumber::Hash(hash) => (header.hash() != hash)
+            .then_some(DownloadError::InvalidTip { received: header.hash(), expected: hash }),
+        BlockHashOrNumber::Number(number) => {
+            (header.number != number).then_some(DownloadError::InvalidTipNumber {
+                received: header.number,
+                expected: number,
+            })
+        }
+    }
+    .or_else(|| {
+        expected_hash
+            .filter(|expected| header.hash() != *expected)
+            .map(|expected| DownloadError::InvalidTip { received: header.hash(), expected })
+    });
 
-    if !valid {
+    if let Some(error) = error {
         client.report_bad_message(peer_id);
-        eyre::bail!(
+        on_bad_peer(peer_id);
+        return Err(eyre::Report::new(error.with_peer(peer_id)).wrap_err(format!(
             "Received invalid header. Received: {:?}. Expected: {:?}",
             header.num_hash(),
             id
-        );
+        )))
     }
 
+    if let Some(chain) = chain {
+        if let Err(error) = validate_header_against_chain_spec(&header, chain) {
+            client.report_bad_message(peer_id);
+            on_bad_peer(peer_id);
+            let error = DownloadError::HeaderValidation { hash: header.hash(), error };
+            return Err(eyre::Report::new(error.with_peer(peer_id)).wrap_err(format!(
+                "Received header inconsistent with the chain spec. Received: {:?}",
+                header.num_hash()
+            )))
+        }
+    }
+
+    client.report_good_message(peer_id);
     Ok(header)
 }
 
+/// Runs basic checks that the header is plausible for the given chain spec, e.g. to detect peers
+/// on a different network.
+///
+/// The header must not use more gas than its limit and must carry a base fee if and only if
+/// London is active at its block number.
+pub fn validate_header_against_chain_spec(
+    header: &SealedHeader,
+    chain: &ChainSpec,
+) -> Result<(), ConsensusError> {
+    if header.gas_used > header.gas_limit {
+        return Err(ConsensusError::HeaderGasUsedExceedsGasLimit {
+            gas_used: header.gas_used,
+            gas_limit: header.gas_limit,
+        })
+    }
+    let london = chain.fork(Hardfork::London).active_at_block(header.number);
+    match (header.base_fee_per_gas.is_some(), london) {
+        (false, true) => Err(ConsensusError::BaseFeeMissing),
+        (true, false) => Err(ConsensusError::BaseFeeUnexpected),
+        _ => Ok(()),
+    }
+}
+
+/// Get a single header from network, re-requesting it up to `max_attempts` times if a request
+/// fails or a peer responds with an invalid header.
+///
+/// Every peer that responded with an invalid header is reported, see [`get_single_header`].
+pub async fn get_single_header_with_retries<Client>(
+    client: Client,
+    id: BlockHashOrNumber,
+    max_attempts: usize,
+) -> Result<SealedHeader>
+where
+    Client: HeadersClient + Clone,
+{
+    let mut errors = Vec::with_capacity(max_attempts);
+    for attempt in 1..=max_attempts {
+        match get_single_header(client.clone(), id).await {
+            Ok(header) => return Ok(header),
+            Err(error) => {
+                info!(target: "reth::cli", ?id, attempt, %error, "Failed to fetch header");
+                errors.push(error.to_string());
+            }
+        }
+    }
+
+    eyre::bail!(
+        "Failed to fetch header {:?} after {} attempts: {}",
+        id,
+        max_attempts,
+        errors.join("; ")
+    )
+}
+
+/// The maximum number of concurrent requests issued by [`get_headers_for_ids`].
+pub const HEADERS_FOR_IDS_CONCURRENCY: usize = 8;
+
+/// Get the headers for the given block ids from network, in the order of the ids.
+///
+/// Up to [`HEADERS_FOR_IDS_CONCURRENCY`] requests are in flight at the same time. Each header is
+/// validated against its requested id, see [`get_single_header`].
+pub async fn get_headers_for_ids<Client>(
+    client: Client,
+    ids: Vec<BlockHashOrNumber>,
+) -> Result<Vec<SealedHeader>>
+where
+    Client: HeadersClient + Clone,
+{
+    stream::iter(ids)
+        .map(|id| get_single_header(client.clone(), id))
+        .buffered(HEADERS_FOR_IDS_CONCURRENCY)
+        .try_collect()
+        .await
+}
+
+/// The number of rows visited per write transaction by [`DbTool::prune_outside`].
+pub const PRUNE_BATCH_SIZE: usize = 10_000;
+
+/// The format of a table export, see [`DbTool::export`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A `key,value` header followed by one row per entry, with the key and value each encoded as
+    /// a quoted JSON string.
+    Csv,
+    /// A JSON array of `{"key": .., "value": ..}` objects.
+    Json,
+}
+
+/// Dispatches a table name to the matching type of [tables].
+///
+/// Evaluates the first expression with the given identifier aliased to the table type, or the
+/// second expression if the name doesn't match any table, e.g.
+/// `table_dispatch!(name, T => tx.clear::<T>(), _ => unreachable!())`.
+macro_rules! table_dispatch {
+    ($name:expr, $table:ident => $body:expr, _ => $unknown:expr) => {
+        $crate::utils::table_dispatch!(@tables $name, $table, $body, $unknown => [
+            CanonicalHeaders,
+            HeaderTD,
+            HeaderNumbers,
+            Headers,
+            BlockBodyIndices,
+            BlockOmmers,
+            BlockWithdrawals,
+            TransactionBlock,
+            Transactions,
+            TxHashNumber,
+            Receipts,
+            PlainAccountState,
+            PlainStorageState,
+            Bytecodes,
+            AccountHistory,
+            StorageHistory,
+            AccountChangeSet,
+            StorageChangeSet,
+            HashedAccount,
+            HashedStorage,
+            AccountsTrie,
+            StoragesTrie,
+            TxSenders,
+            SyncStage,
+            SyncStageProgress
+        ])
+    };
+    (@tables $name:expr, $table:ident, $body:expr, $unknown:expr => [$($t:ident),*]) => {
+        match $name {
+            $(stringify!($t) => {
+                type $table = reth_db::tables::$t;
+                $body
+            })*
+            _ => $unknown,
+        }
+    };
+}
+
+pub(crate) use table_dispatch;
+
+/// Errors returned by [DbTool].
+///
+/// Converts into an [eyre::Report] for the CLI, while library consumers can match on the error
+/// kind.
+#[derive(thiserror::Error, Debug)]
+pub enum DbToolError {
+    /// Failed to open a transaction or cursor, or to read, write or decode an entry.
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+    /// Failed to read from the provider.
+    #[error(transparent)]
+    Provider(#[from] reth_interfaces::Error),
+    /// Failed to execute a block.
+    #[error(transparent)]
+    Execution(#[from] BlockExecutionError),
+    /// The given table names do not exist.
+    #[error("Unknown tables: {}", .0.join(", "))]
+    UnknownTables(Vec<String>),
+    /// The block is not in the database.
+    #[error("Block {0} not found")]
+    BlockNotFound(BlockNumber),
+    /// The total difficulty of the block is not in the database.
+    #[error("Total difficulty of block {0} not found")]
+    TotalDifficultyNotFound(BlockNumber),
+    /// The genesis block has no parent state to be executed on.
+    #[error("The genesis block can not be re-executed")]
+    GenesisExecution,
+    /// Failed to remove the database directory.
+    #[error("Dropping the database failed")]
+    DropDatabase(#[source] std::io::Error),
+    /// Failed to write exported entries.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Failed to serialize exported entries.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// A destructive operation was called without the explicit `dangerous` opt-in.
+    #[error("Refusing to run `{0}` without the dangerous opt-in")]
+    DangerousNotEnabled(&'static str),
+}
+
 /// Wrapper over DB that implements many useful DB queries.
 pub struct DbTool<'a, DB: Database> {
     pub(crate) db: &'a DB,
@@ -64,7 +398,7 @@ pub struct DbTool<'a, DB: Database> {
 
 impl<'a, DB: Database> DbTool<'a, DB> {
     /// Takes a DB where the tables have already been created.
-    pub(crate) fn new(db: &'a DB, chain: Arc<ChainSpec>) -> eyre::Result<Self> {
+    pub(crate) fn new(db: &'a DB, chain: Arc<ChainSpec>) -> Result<Self, DbToolError> {
         Ok(Self { db, chain })
     }
 
@@ -75,38 +409,560 @@ impl<'a, DB: Database> DbTool<'a, DB> {
         skip: usize,
         len: usize,
         reverse: bool,
-    ) -> Result<Vec<(T::Key, T::Value)>> {
-        let data = self.db.view(|tx| {
-            let mut cursor = tx.cursor_read::<T>().expect("Was not able to obtain a cursor.");
+    ) -> Result<Vec<(T::Key, T::Value)>, DbToolError> {
+        Ok(self.db.view(|tx| {
+            let mut cursor = tx.cursor_read::<T>()?;
 
             if reverse {
                 cursor.walk_back(None)?.skip(skip).take(len).collect::<Result<_, _>>()
             } else {
                 cursor.walk(None)?.skip(skip).take(len).collect::<Result<_, _>>()
             }
-        })?;
+        })??)
+    }
 
-        data.map_err(|e| eyre::eyre!(e))
+    /// Grabs the entries of the table with keys from `start` to `end`, which is included if
+    /// `inclusive` is set.
+    ///
+    /// Unlike [`DbTool::list`], this seeks directly to `start` instead of walking the table from
+    /// the beginning. If `reverse` is set, the entries are returned in descending key order.
+    pub fn list_range<T: Table>(
+        &mut self,
+        start: T::Key,
+        end: T::Key,
+        inclusive: bool,
+        reverse: bool,
+    ) -> Result<Vec<(T::Key, T::Value)>, DbToolError> {
+        let end = if inclusive { Bound::Included(end) } else { Bound::Excluded(end) };
+        let mut entries = self.db.view(|tx| {
+            let mut cursor = tx.cursor_read::<T>()?;
+            cursor.walk_range((Bound::Included(start), end))?.collect::<Result<Vec<_>, _>>()
+        })??;
+        if reverse {
+            entries.reverse();
+        }
+        Ok(entries)
     }
 
     /// Grabs the content of the table for the given key
-    pub fn get<T: Table>(&mut self, key: T::Key) -> Result<Option<T::Value>> {
-        self.db.view(|tx| tx.get::<T>(key))?.map_err(|e| eyre::eyre!(e))
+    pub fn get<T: Table>(&mut self, key: T::Key) -> Result<Option<T::Value>, DbToolError> {
+        Ok(self.db.view(|tx| tx.get::<T>(key))??)
+    }
+
+    /// Grabs all duplicate values of the given key of a [DupSort] table, ordered by their subkeys.
+    ///
+    /// Returns an empty list if the key is not present.
+    pub fn list_dups<T: DupSort>(&mut self, key: T::Key) -> Result<Vec<T::Value>, DbToolError> {
+        Ok(self.db.view(|tx| {
+            let mut cursor = tx.cursor_dup_read::<T>()?;
+            cursor
+                .walk_dup(Some(key), None)?
+                .map(|entry| entry.map(|(_, value)| value))
+                .collect::<Result<Vec<_>, _>>()
+        })??)
+    }
+
+    /// Grabs a page of at most `len` entries of the table with keys after `after`, or from the
+    /// beginning of the table if `after` is `None`.
+    ///
+    /// Unlike [`DbTool::list`], this seeks directly past `after` instead of skipping entries. The
+    /// returned key is the resume token for the next page, which is `None` once the end of the
+    /// table is reached.
+    pub fn list_paged<T: Table>(
+        &mut self,
+        after: Option<T::Key>,
+        len: usize,
+    ) -> Result<(Vec<(T::Key, T::Value)>, Option<T::Key>), DbToolError> {
+        let mut entries = self.db.view(|tx| {
+            let mut cursor = tx.cursor_read::<T>()?;
+            // read one more entry to find out if there is a next page
+            let take = len.saturating_add(1);
+            match after {
+                Some(after) => cursor
+                    .walk_range((Bound::Excluded(after), Bound::Unbounded))?
+                    .take(take)
+                    .collect::<Result<Vec<_>, _>>(),
+                None => cursor.walk(None)?.take(take).collect::<Result<Vec<_>, _>>(),
+            }
+        })??;
+        let next = if entries.len() > len {
+            entries.truncate(len);
+            entries.last().map(|(key, _)| key.clone())
+        } else {
+            None
+        };
+        Ok((entries, next))
+    }
+
+    /// Runs the closure with a single read transaction, so that all reads within it see the same
+    /// state of the database.
+    pub fn with_read_tx<R>(
+        &self,
+        f: impl FnOnce(&<DB as DatabaseGAT<'_>>::TX) -> Result<R, DbToolError>,
+    ) -> Result<R, DbToolError> {
+        self.db.view(f)?
+    }
+
+    /// Grabs the values of the table for all of the given keys in a single read transaction.
+    ///
+    /// The keys are returned in the given order, paired with `None` if they are not present.
+    pub fn get_many<T: Table>(
+        &mut self,
+        keys: Vec<T::Key>,
+    ) -> Result<Vec<(T::Key, Option<T::Value>)>, DbToolError> {
+        Ok(self.db.view(|tx| {
+            keys.into_iter()
+                .map(|key| Ok((key.clone(), tx.get::<T>(key)?)))
+                .collect::<Result<Vec<_>, DatabaseError>>()
+        })??)
+    }
+
+    /// Returns the number of entries in the table, without reading them.
+    pub fn count<T: Table>(&mut self) -> Result<usize, DbToolError> {
+        Ok(self.db.view(|tx| tx.entries::<T>())??)
+    }
+
+    /// Counts the encoded sizes of the values of the table into the given buckets.
+    ///
+    /// `buckets` are the inclusive upper bounds of the buckets in ascending order. A value is
+    /// counted into the first bucket it fits into, values larger than the last bound are counted
+    /// into a trailing `usize::MAX` bucket. The table is streamed without decoding the values.
+    pub fn value_size_histogram<T: Table>(
+        &mut self,
+        buckets: &[usize],
+    ) -> Result<Vec<(usize, u64)>, DbToolError> {
+        let mut histogram = buckets
+            .iter()
+            .copied()
+            .chain(std::iter::once(usize::MAX))
+            .map(|bound| (bound, 0))
+            .collect::<Vec<_>>();
+        self.db.view(|tx| {
+            let mut cursor = tx.cursor_read::<RawTable<T>>()?;
+            for entry in cursor.walk(None)? {
+                let (_, value) = entry?;
+                let size = value.compress().len();
+                histogram[buckets.partition_point(|bound| *bound < size)].1 += 1;
+            }
+            Ok::<_, DatabaseError>(())
+        })??;
+        Ok(histogram)
+    }
+
+    /// Writes the entries of the table to the writer in the given format, returning the number of
+    /// exported entries.
+    ///
+    /// The entries are streamed from the table, walking it in reverse if `reverse` is set, and at
+    /// most `limit` entries are exported.
+    pub fn export<T: Table, W: Write>(
+        &mut self,
+        writer: W,
+        format: ExportFormat,
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<usize, DbToolError>
+    where
+        T::Key: Serialize,
+    {
+        self.db.view(|tx| {
+            let mut cursor = tx.cursor_read::<T>()?;
+            let limit = limit.unwrap_or(usize::MAX);
+            if reverse {
+                export_entries(cursor.walk_back(None)?.take(limit), writer, format)
+            } else {
+                export_entries(cursor.walk(None)?.take(limit), writer, format)
+            }
+        })?
+    }
+
+    /// Walks the keys of the table and returns the first pair of adjacent keys that is not strictly
+    /// increasing, or `None` if all keys are.
+    ///
+    /// Entries are stored in the order of their encoded keys, so this catches keys whose decoded
+    /// order disagrees with their encoding, as well as repeated keys of dupsort tables.
+    pub fn scan_monotonic<T: Table>(&mut self) -> Result<Option<(T::Key, T::Key)>, DbToolError> {
+        Ok(self.db.view(|tx| {
+            let mut cursor = tx.cursor_read::<T>()?;
+            let mut prev: Option<T::Key> = None;
+            for entry in cursor.walk(None)? {
+                let (key, _) = entry?;
+                if let Some(prev) = prev.take().filter(|prev| *prev >= key) {
+                    return Ok(Some((prev, key)))
+                }
+                prev = Some(key);
+            }
+            Ok::<_, DatabaseError>(None)
+        })??)
+    }
+
+    /// Compares the table against the same table of another database.
+    ///
+    /// Both tables are streamed side by side in the order of their encoded keys, so neither is
+    /// loaded into memory. Values are compared in their encoded form. Entries of dupsort tables
+    /// are paired up in walk order, so this is best suited for tables with unique keys.
+    pub fn diff_table<T: Table, DB2: Database>(
+        &mut self,
+        other: &DbTool<'_, DB2>,
+    ) -> Result<TableDiff<T>, DbToolError> {
+        Ok(self.db.view(|tx| {
+            other.db.view(|other_tx| {
+                let mut cursor = tx.cursor_read::<RawTable<T>>()?;
+                let mut other_cursor = other_tx.cursor_read::<RawTable<T>>()?;
+                let mut ours = cursor.walk(None)?;
+                let mut theirs = other_cursor.walk(None)?;
+
+                let mut diff = TableDiff::default();
+                let mut our_entry = ours.next().transpose()?;
+                let mut their_entry = theirs.next().transpose()?;
+                loop {
+                    match (our_entry.take(), their_entry.take()) {
+                        (None, None) => break,
+                        (Some((key, _)), None) => {
+                            diff.only_in_self.push(key.key()?);
+                            our_entry = ours.next().transpose()?;
+                        }
+                        (None, Some((key, _))) => {
+                            diff.only_in_other.push(key.key()?);
+                            their_entry = theirs.next().transpose()?;
+                        }
+                        (Some(ours_kv), Some(theirs_kv)) => match ours_kv.0.cmp(&theirs_kv.0) {
+                            Ordering::Less => {
+                                diff.only_in_self.push(ours_kv.0.key()?);
+                                our_entry = ours.next().transpose()?;
+                                their_entry = Some(theirs_kv);
+                            }
+                            Ordering::Greater => {
+                                diff.only_in_other.push(theirs_kv.0.key()?);
+                                our_entry = Some(ours_kv);
+                                their_entry = theirs.next().transpose()?;
+                            }
+                            Ordering::Equal => {
+                                if ours_kv.1 != theirs_kv.1 {
+                                    diff.changed.push(ours_kv.0.key()?);
+                                }
+                                our_entry = ours.next().transpose()?;
+                                their_entry = theirs.next().transpose()?;
+                            }
+                        },
+                    }
+                }
+                Ok::<_, DatabaseError>(diff)
+            })?
+        })??)
     }
 
     /// Drops the database at the given path.
-    pub fn drop(&mut self, path: impl AsRef<Path>) -> Result<()> {
+    pub fn drop(&mut self, path: impl AsRef<Path>) -> Result<(), DbToolError> {
         let path = path.as_ref();
         info!(target: "reth::cli", "Dropping database at {:?}", path);
-        std::fs::remove_dir_all(path).wrap_err("Dropping the database failed")?;
+        std::fs::remove_dir_all(path).map_err(DbToolError::DropDatabase)?;
         Ok(())
     }
 
     /// Drops the provided table from the database.
-    pub fn drop_table<T: Table>(&mut self) -> Result<()> {
+    pub fn drop_table<T: Table>(&mut self) -> Result<(), DbToolError> {
         self.db.update(|tx| tx.clear::<T>())??;
         Ok(())
     }
+
+    /// Returns the number of entries [DbTool::drop_table] would delete, without deleting them.
+    pub fn drop_table_dry_run<T: Table>(&mut self) -> Result<usize, DbToolError> {
+        self.count::<T>()
+    }
+
+    /// Clears all of the tables with the given names in a single write transaction, so they are
+    /// either all cleared or not at all.
+    ///
+    /// Returns [DbToolError::UnknownTables] listing the unknown table names, if any, before
+    /// clearing anything.
+    pub fn drop_tables(&mut self, names: &[&str]) -> Result<(), DbToolError> {
+        let unknown = names
+            .iter()
+            .filter(|name| !tables::TABLES.iter().any(|(_, table)| table == *name))
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        if !unknown.is_empty() {
+            return Err(DbToolError::UnknownTables(unknown))
+        }
+
+        self.db.update(|tx| {
+            for name in names {
+                table_dispatch!(*name, T => tx.clear::<T>(), _ => {
+                    unreachable!("table names are validated")
+                })?;
+            }
+            Ok::<_, DatabaseError>(())
+        })??;
+        Ok(())
+    }
+
+    /// Deletes all rows of the table whose block, as returned by `key_to_block`, is outside of the
+    /// `keep` range. Returns the number of deleted rows.
+    ///
+    /// The table is walked in batches of [`PRUNE_BATCH_SIZE`] rows, each committed in its own write
+    /// transaction. A batch may end in the middle of the duplicates of a key in dupsort tables.
+    ///
+    /// CAUTION: this permanently deletes data and is not coordinated with the stages, so the
+    /// pruned table may no longer be consistent with the rest of the database. Nothing is deleted
+    /// unless `dangerous` is set, otherwise [DbToolError::DangerousNotEnabled] is returned.
+    pub fn prune_outside<T: Table>(
+        &mut self,
+        keep: RangeInclusive<u64>,
+        key_to_block: impl Fn(&T::Key) -> u64,
+        dangerous: bool,
+    ) -> Result<usize, DbToolError> {
+        if !dangerous {
+            return Err(DbToolError::DangerousNotEnabled("prune_outside"))
+        }
+
+        let mut deleted = 0;
+        let mut start_key = None;
+        loop {
+            let (batch_deleted, next_key) = self.db.update(|tx| {
+                let mut cursor = tx.cursor_write::<T>()?;
+                let mut walker = cursor.walk(start_key.clone())?;
+                let mut batch_deleted = 0;
+                let mut visited = 0;
+                while let Some((key, _)) = walker.next().transpose()? {
+                    let prune = !keep.contains(&key_to_block(&key));
+                    // the walk restarts at the first duplicate of the key the previous batch
+                    // stopped at, so the already visited duplicates of a kept key are skipped
+                    if !prune && start_key.as_ref() == Some(&key) {
+                        continue
+                    }
+                    if visited == PRUNE_BATCH_SIZE {
+                        return Ok((batch_deleted, Some(key)))
+                    }
+                    visited += 1;
+                    if prune {
+                        walker.delete_current()?;
+                        batch_deleted += 1;
+                    }
+                }
+                Ok::<_, DatabaseError>((batch_deleted, None))
+            })??;
+
+            deleted += batch_deleted;
+            match next_key {
+                Some(key) => start_key = Some(key),
+                None => return Ok(deleted),
+            }
+        }
+    }
+
+    /// Recomputes the hash of every stored header in the given range and compares it against the
+    /// [CanonicalHeaders](tables::CanonicalHeaders) mapping, returning the numbers of all blocks
+    /// whose hashes don't match.
+    ///
+    /// Blocks without a canonical hash are skipped, blocks with a canonical hash but without a
+    /// stored header are reported as mismatches.
+    pub fn verify_header_hashes(
+        &mut self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<BlockNumber>, DbToolError> {
+        let mismatches = self.db.view(|tx| {
+            let mut canonical = tx.cursor_read::<tables::CanonicalHeaders>()?;
+            let mut mismatches = Vec::new();
+            for entry in canonical.walk_range(range)? {
+                let (number, hash) = entry?;
+                let header = tx.get::<tables::Headers>(number)?;
+                if header.map(|header| header.seal_slow().hash()) != Some(hash) {
+                    mismatches.push(number);
+                }
+            }
+            Ok::<_, DatabaseError>(mismatches)
+        })??;
+        Ok(mismatches)
+    }
+
+    /// Removes all canonical header entries above the given block, returning the number of removed
+    /// entries.
+    ///
+    /// This is the manual counterpart to a pipeline unwind, meant for recovering a canonical chain
+    /// with gaps, so that the node can re-sync from the given block.
+    ///
+    /// CAUTION: only the [CanonicalHeaders](tables::CanonicalHeaders) table is unwound. Headers,
+    /// bodies, state and stage checkpoints above the given block are left untouched and may need
+    /// to be unwound as well. Nothing is removed unless `dangerous` is set, otherwise
+    /// [DbToolError::DangerousNotEnabled] is returned.
+    pub fn unwind_canonical_to(
+        &mut self,
+        block: BlockNumber,
+        dangerous: bool,
+    ) -> Result<usize, DbToolError> {
+        if !dangerous {
+            return Err(DbToolError::DangerousNotEnabled("unwind_canonical_to"))
+        }
+        // there are no entries above the largest block number
+        let Some(first_removed) = block.checked_add(1) else { return Ok(0) };
+
+        let removed = self.db.update(|tx| {
+            let mut cursor = tx.cursor_write::<tables::CanonicalHeaders>()?;
+            let mut walker = cursor.walk(Some(first_removed))?;
+            let mut removed = 0;
+            while walker.next().transpose()?.is_some() {
+                walker.delete_current()?;
+                removed += 1;
+            }
+            Ok::<_, DatabaseError>(removed)
+        })??;
+        Ok(removed)
+    }
+
+    /// Returns up to `n` of the most recent canonical blocks, starting at the canonical tip and
+    /// walking backwards.
+    ///
+    /// Returns fewer blocks if the chain is shorter than `n` or if a block body is missing.
+    pub fn recent_blocks(&mut self, n: usize) -> Result<Vec<SealedBlock>, DbToolError> {
+        let factory = ProviderFactory::new(self.db, self.chain.clone());
+        let tip = factory.last_block_number()?;
+
+        let mut blocks = Vec::new();
+        for number in (0..=tip).rev().take(n) {
+            let Some(block) = factory.block(number.into())? else { break };
+            blocks.push(block.seal_slow());
+        }
+
+        Ok(blocks)
+    }
+
+    /// Re-executes the given block on top of its parent state and compares the produced receipts
+    /// against the receipts stored in the database.
+    ///
+    /// Receipts are compared by status, cumulative gas used and logs bloom. Returns every receipt
+    /// that differs, an empty list means the stored receipts are consistent with the block.
+    pub fn verify_block_receipts<EF: ExecutorFactory>(
+        &mut self,
+        number: BlockNumber,
+        executor_factory: &EF,
+    ) -> Result<Vec<ReceiptMismatch>, DbToolError> {
+        if number == 0 {
+            return Err(DbToolError::GenesisExecution)
+        }
+
+        let factory = ProviderFactory::new(self.db, self.chain.clone());
+        let block = factory.block(number.into())?.ok_or(DbToolError::BlockNotFound(number))?;
+        let total_difficulty = factory
+            .header_td_by_number(number)?
+            .ok_or(DbToolError::TotalDifficultyNotFound(number))?;
+        let stored = factory.receipts_by_block(number.into())?.unwrap_or_default();
+
+        let mut executor = executor_factory.with_sp(factory.history_by_block_number(number - 1)?);
+        let post_state = executor.execute(&block, total_difficulty, None)?;
+        let executed = post_state.receipts(number);
+
+        let mismatches = (0..stored.len().max(executed.len()))
+            .filter_map(|index| {
+                let stored = stored.get(index);
+                let executed = executed.get(index);
+                let matches = match (stored, executed) {
+                    (Some(stored), Some(executed)) => {
+                        stored.success == executed.success &&
+                            stored.cumulative_gas_used == executed.cumulative_gas_used &&
+                            stored.bloom_slow() == executed.bloom_slow()
+                    }
+                    _ => false,
+                };
+                (!matches).then(|| ReceiptMismatch {
+                    index,
+                    stored: stored.cloned(),
+                    executed: executed.cloned(),
+                })
+            })
+            .collect();
+
+        Ok(mismatches)
+    }
+}
+
+/// The keys that differ between the same table of two databases.
+///
+/// See [DbTool::diff_table].
+pub struct TableDiff<T: Table> {
+    /// Keys that are only present in the compared database.
+    pub only_in_self: Vec<T::Key>,
+    /// Keys that are only present in the other database.
+    pub only_in_other: Vec<T::Key>,
+    /// Keys that are present in both databases, but with different values.
+    pub changed: Vec<T::Key>,
+}
+
+impl<T: Table> TableDiff<T> {
+    /// Returns `true` if both tables are identical.
+    pub fn is_empty(&self) -> bool {
+        self.only_in_self.is_empty() && self.only_in_other.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl<T: Table> Default for TableDiff<T> {
+    fn default() -> Self {
+        Self { only_in_self: Vec::new(), only_in_other: Vec::new(), changed: Vec::new() }
+    }
+}
+
+impl<T: Table> std::fmt::Debug for TableDiff<T>
+where
+    T::Key: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TableDiff")
+            .field("only_in_self", &self.only_in_self)
+            .field("only_in_other", &self.only_in_other)
+            .field("changed", &self.changed)
+            .finish()
+    }
+}
+
+/// A receipt that differs between the database and a re-execution of its block.
+///
+/// See [DbTool::verify_block_receipts].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptMismatch {
+    /// The index of the transaction within the block.
+    pub index: usize,
+    /// The receipt stored in the database, if any.
+    pub stored: Option<Receipt>,
+    /// The receipt produced by re-executing the block, if any.
+    pub executed: Option<Receipt>,
+}
+
+/// Writes the entries to the writer in the given format, see [`DbTool::export`].
+fn export_entries<K: Serialize, V: Serialize, W: Write>(
+    entries: impl Iterator<Item = Result<(K, V), DatabaseError>>,
+    mut writer: W,
+    format: ExportFormat,
+) -> Result<usize, DbToolError> {
+    let mut count = 0;
+    match format {
+        ExportFormat::Csv => {
+            let quote = |field: String| format!("\"{}\"", field.replace('"', "\"\""));
+            writeln!(writer, "key,value")?;
+            for entry in entries {
+                let (key, value) = entry?;
+                let key = quote(serde_json::to_string(&key)?);
+                let value = quote(serde_json::to_string(&value)?);
+                writeln!(writer, "{key},{value}")?;
+                count += 1;
+            }
+        }
+        ExportFormat::Json => {
+            write!(writer, "[")?;
+            for entry in entries {
+                let (key, value) = entry?;
+                if count > 0 {
+                    write!(writer, ",")?;
+                }
+                serde_json::to_writer(
+                    &mut writer,
+                    &serde_json::json!({ "key": key, "value": value }),
+                )?;
+                count += 1;
+            }
+            writeln!(writer, "]")?;
+        }
+    }
+    writer.flush()?;
+    Ok(count)
 }
 
 /// Parses a user-specified path with support for environment variables and common shorthands (e.g.
@@ -114,3 +970,730 @@ impl<'a, DB: Database> DbTool<'a, DB> {
 pub fn parse_path(value: &str) -> Result<PathBuf, shellexpand::LookupError<VarError>> {
     shellexpand::full(value).map(|path| PathBuf::from(path.into_owned()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::mdbx::test_utils::create_test_rw_db;
+    use reth_interfaces::test_utils::{
+        generators::{random_block, random_block_range},
+        OfflineHeadersClient, TestHeadersClient,
+    };
+    use reth_primitives::{Address, Bytes, Header, Log, StorageEntry, TxType, H256, MAINNET, U256};
+    use reth_provider::{insert_canonical_block, test_utils::TestExecutorFactory, PostState};
+    use std::time::Duration;
+
+    /// Inserts canonical hashes for blocks `0..count`.
+    fn insert_canonical_headers<DB: Database>(db: &DB, count: u64) {
+        db.update(|tx| {
+            for number in 0..count {
+                tx.put::<tables::CanonicalHeaders>(number, H256::from_low_u64_be(number))?;
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+    }
+
+    /// Inserts an empty genesis block and block 1 with one transaction per receipt, storing the
+    /// given receipts for block 1.
+    fn insert_block_with_receipts<DB: Database>(db: &DB, receipts: &[Receipt]) {
+        db.update(|tx| {
+            let genesis = random_block(0, None, Some(0), Some(0));
+            let block = random_block(1, Some(genesis.hash()), Some(receipts.len() as u8), Some(0));
+            insert_canonical_block(tx, genesis, None)?;
+            let indices = insert_canonical_block(tx, block, None)?;
+            for (tx_num, receipt) in indices.tx_num_range().zip(receipts) {
+                tx.put::<tables::Receipts>(tx_num, receipt.clone())?;
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+    }
+
+    /// Returns an executor factory that produces the given receipts for block 1.
+    fn executor_with_receipts(receipts: &[Receipt]) -> TestExecutorFactory {
+        let mut post_state = PostState::default();
+        for receipt in receipts {
+            post_state.add_receipt(1, receipt.clone());
+        }
+        let executor_factory = TestExecutorFactory::new(MAINNET.clone());
+        executor_factory.extend(vec![post_state]);
+        executor_factory
+    }
+
+    fn receipt(index: u64) -> Receipt {
+        Receipt {
+            tx_type: TxType::EIP1559,
+            success: true,
+            cumulative_gas_used: 21_000 * (index + 1),
+            logs: vec![Log {
+                address: Address::from_low_u64_be(index),
+                topics: vec![H256::from_low_u64_be(index)],
+                data: Bytes::default(),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn get_single_header_retries_invalid_response() {
+        let client = TestHeadersClient::default();
+        let headers = [4, 5].map(|number| Header { number, ..Default::default() });
+        client.extend(headers.clone()).await;
+
+        let header = get_single_header_with_retries(client.clone(), 5.into(), 3).await.unwrap();
+        assert_eq!(header, headers[1].clone().seal_slow());
+        assert_eq!(client.request_attempts(), 2);
+
+        // the client has no headers left
+        let err = get_single_header_with_retries(client.clone(), 5.into(), 2).await.unwrap_err();
+        assert!(err.to_string().contains("after 2 attempts"));
+        assert_eq!(client.request_attempts(), 4);
+    }
+
+    #[tokio::test]
+    async fn get_single_header_reports_good_message() {
+        let client = TestHeadersClient::default();
+        client.extend([Header { number: 1, ..Default::default() }]).await;
+
+        get_single_header(client.clone(), 1.into()).await.unwrap();
+        assert_eq!(client.good_messages(), 1);
+
+        // no headers left, so the peer is not rewarded
+        get_single_header(client.clone(), 1.into()).await.unwrap_err();
+        assert_eq!(client.good_messages(), 1);
+    }
+
+    #[tokio::test]
+    async fn get_single_header_error_carries_peer_id() {
+        let client = TestHeadersClient::default();
+        client.extend([Header { number: 2, ..Default::default() }]).await;
+
+        let err = get_single_header(client.clone(), 1.into()).await.unwrap_err();
+        let error = err.downcast_ref::<DownloadError>().unwrap();
+        // the test client attributes all responses to the default peer
+        assert_eq!(error.peer_id(), Some(PeerId::default()));
+        assert_eq!(
+            error,
+            &DownloadError::InvalidTipNumber { received: 2, expected: 1 }
+                .with_peer(PeerId::default())
+        );
+    }
+
+    #[tokio::test]
+    async fn get_single_header_invokes_bad_peer_hook() {
+        let peer_id = PeerId::from_low_u64_be(7);
+        let client = TestHeadersClient::default().with_peer_id(peer_id);
+        client.extend([1, 2].map(|number| Header { number, ..Default::default() })).await;
+
+        let mut bad_peers = Vec::new();
+        let header = get_single_header_with_bad_peer_hook(client.clone(), 1.into(), |peer| {
+            bad_peers.push(peer)
+        })
+        .await
+        .unwrap();
+        assert_eq!(header.number, 1);
+        assert!(bad_peers.is_empty());
+
+        // the remaining header does not match the requested number
+        get_single_header_with_bad_peer_hook(client, 1.into(), |peer| bad_peers.push(peer))
+            .await
+            .unwrap_err();
+        assert_eq!(bad_peers, vec![peer_id]);
+    }
+
+    #[tokio::test]
+    async fn get_single_header_checked_rejects_wrong_hash() {
+        let client = TestHeadersClient::default();
+        let header = Header { number: 1, ..Default::default() };
+        let forged = Header { gas_limit: 1, ..header.clone() };
+        let expected = header.clone().seal_slow();
+        client.extend([header, forged.clone()]).await;
+
+        let fetched = get_single_header_checked(client.clone(), 1, expected.hash()).await.unwrap();
+        assert_eq!(fetched, expected);
+
+        // the second header has the right number, but not the checkpoint hash
+        let err = get_single_header_checked(client, 1, expected.hash()).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<DownloadError>(),
+            Some(
+                &DownloadError::InvalidTip {
+                    received: forged.hash_slow(),
+                    expected: expected.hash()
+                }
+                .with_peer(PeerId::default())
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn get_single_header_fails_offline() {
+        let err = get_single_header(OfflineHeadersClient, 1.into()).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<RequestError>(), Some(&RequestError::NetworkDisabled));
+        assert_eq!(err.to_string(), "Network is disabled.");
+    }
+
+    #[tokio::test]
+    async fn get_single_header_returns_by_deadline() {
+        let client = TestHeadersClient::default();
+        client.extend([Header { number: 1, ..Default::default() }]).await;
+        client.drop_next_response();
+
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let err = get_single_header_with_deadline(client.clone(), 1.into(), deadline).await;
+        assert!(Instant::now() >= deadline);
+        assert_eq!(
+            err.unwrap_err().downcast_ref::<DownloadError>(),
+            Some(&DownloadError::RequestError(RequestError::Timeout))
+        );
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let header = get_single_header_with_deadline(client, 1.into(), deadline).await;
+        assert_eq!(header.unwrap().number, 1);
+    }
+
+    #[tokio::test]
+    async fn get_single_header_validates_chain_spec() {
+        // London activates at this block on mainnet
+        let number = 12_965_000;
+        let client = TestHeadersClient::default();
+        client
+            .extend([
+                Header { number, ..Default::default() },
+                Header { number, base_fee_per_gas: Some(1_000_000_000), ..Default::default() },
+            ])
+            .await;
+
+        let err =
+            get_single_header_validated(client.clone(), number.into(), &MAINNET).await.unwrap_err();
+        let error = err.downcast_ref::<DownloadError>().unwrap();
+        // the test client attributes all responses to the default peer
+        assert_eq!(error.peer_id(), Some(PeerId::default()));
+        assert_eq!(
+            error,
+            &DownloadError::HeaderValidation {
+                hash: Header { number, ..Default::default() }.hash_slow(),
+                error: ConsensusError::BaseFeeMissing,
+            }
+            .with_peer(PeerId::default())
+        );
+        assert_eq!(client.good_messages(), 0);
+
+        let header =
+            get_single_header_validated(client.clone(), number.into(), &MAINNET).await.unwrap();
+        assert_eq!(header.base_fee_per_gas, Some(1_000_000_000));
+
+        // the base fee is unexpected before London
+        let header = Header { number: number - 1, base_fee_per_gas: Some(7), ..Default::default() };
+        assert_eq!(
+            validate_header_against_chain_spec(&header.seal_slow(), &MAINNET),
+            Err(ConsensusError::BaseFeeUnexpected)
+        );
+    }
+
+    #[tokio::test]
+    async fn get_single_header_direction() {
+        let client = TestHeadersClient::default();
+        let header = Header { number: 1, ..Default::default() };
+        client.extend([header.clone(), header.clone()]).await;
+
+        get_single_header(client.clone(), 1.into()).await.unwrap();
+        get_single_header_in_direction(client.clone(), 1.into(), HeadersDirection::Falling)
+            .await
+            .unwrap();
+
+        let directions = client.request_log().into_iter().map(|request| request.direction);
+        assert_eq!(
+            directions.collect::<Vec<_>>(),
+            vec![HeadersDirection::Rising, HeadersDirection::Falling]
+        );
+    }
+
+    #[tokio::test]
+    async fn get_headers_for_multiple_ids() {
+        let client = TestHeadersClient::default();
+        let headers = [1, 2, 3].map(|number| Header { number, ..Default::default() });
+        client.extend(headers.clone()).await;
+
+        let ids = vec![1.into(), 2.into(), 3.into()];
+        let fetched = get_headers_for_ids(client.clone(), ids).await.unwrap();
+        assert_eq!(fetched, headers.map(|header| header.seal_slow()).to_vec());
+        assert_eq!(client.request_attempts(), 3);
+
+        // a header that doesn't match its id fails the batch
+        client.extend([Header { number: 7, ..Default::default() }]).await;
+        assert!(get_headers_for_ids(client, vec![8.into()]).await.is_err());
+    }
+
+    #[test]
+    fn export_headers() {
+        let db = create_test_rw_db();
+        db.update(|tx| {
+            for number in 0..3 {
+                tx.put::<tables::Headers>(number, Header { number, ..Default::default() })?;
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+
+        let mut json = Vec::new();
+        let count =
+            tool.export::<tables::Headers, _>(&mut json, ExportFormat::Json, false, None).unwrap();
+        assert_eq!(count, 3);
+        let entries: Vec<serde_json::Value> = serde_json::from_slice(&json).unwrap();
+        for (number, entry) in entries.into_iter().enumerate() {
+            let header: Header = serde_json::from_value(entry["value"].clone()).unwrap();
+            assert_eq!(entry["key"], number);
+            assert_eq!(header, Header { number: number as u64, ..Default::default() });
+        }
+
+        let mut csv = Vec::new();
+        let count =
+            tool.export::<tables::Headers, _>(&mut csv, ExportFormat::Csv, true, Some(2)).unwrap();
+        assert_eq!(count, 2);
+        let csv = String::from_utf8(csv).unwrap();
+        let keys = csv.lines().skip(1).map(|line| line.split(',').next().unwrap());
+        assert_eq!(keys.collect::<Vec<_>>(), vec!["\"2\"", "\"1\""]);
+    }
+
+    #[test]
+    fn drop_multiple_tables() {
+        let db = create_test_rw_db();
+        insert_canonical_headers(&*db, 3);
+        db.update(|tx| {
+            for number in 0..3 {
+                tx.put::<tables::Headers>(number, Header { number, ..Default::default() })?;
+                tx.put::<tables::HeaderTD>(number, U256::from(number).into())?;
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+
+        let err = tool.drop_tables(&["Headers", "Foo", "Bar"]).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown tables: Foo, Bar");
+        assert!(
+            matches!(&err, DbToolError::UnknownTables(names) if names == &["Foo", "Bar"]),
+            "{err:?}"
+        );
+        assert_eq!(tool.count::<tables::Headers>().unwrap(), 3);
+
+        tool.drop_tables(&["CanonicalHeaders", "Headers"]).unwrap();
+        assert_eq!(tool.count::<tables::CanonicalHeaders>().unwrap(), 0);
+        assert_eq!(tool.count::<tables::Headers>().unwrap(), 0);
+        assert_eq!(tool.count::<tables::HeaderTD>().unwrap(), 3);
+    }
+
+    #[test]
+    fn drop_table_dry_run() {
+        let db = create_test_rw_db();
+        insert_canonical_headers(&*db, 5);
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+
+        let expected = tool.drop_table_dry_run::<tables::CanonicalHeaders>().unwrap();
+        assert_eq!(tool.count::<tables::CanonicalHeaders>().unwrap(), expected);
+
+        tool.drop_table::<tables::CanonicalHeaders>().unwrap();
+        assert_eq!(expected, 5);
+        assert_eq!(tool.count::<tables::CanonicalHeaders>().unwrap(), 0);
+    }
+
+    #[test]
+    fn list_paged() {
+        let db = create_test_rw_db();
+        insert_canonical_headers(&*db, 8);
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+
+        let mut pages = Vec::new();
+        let mut after = None;
+        loop {
+            let (entries, next) = tool.list_paged::<tables::CanonicalHeaders>(after, 3).unwrap();
+            pages.push(entries.into_iter().map(|(number, _)| number).collect::<Vec<_>>());
+            match next {
+                Some(next) => after = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(pages, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6, 7]]);
+    }
+
+    #[test]
+    fn scan_monotonic_keys() {
+        let db = create_test_rw_db();
+        insert_canonical_headers(&*db, 5);
+        let (first, second) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        db.update(|tx| {
+            // a repeated key is not strictly increasing
+            for (address, slot) in [(first, 1), (second, 1), (second, 2)] {
+                let entry = StorageEntry { key: H256::from_low_u64_be(slot), value: U256::ZERO };
+                tx.put::<tables::PlainStorageState>(address, entry)?;
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+
+        assert_eq!(tool.scan_monotonic::<tables::CanonicalHeaders>().unwrap(), None);
+        assert_eq!(tool.scan_monotonic::<tables::Headers>().unwrap(), None);
+        assert_eq!(
+            tool.scan_monotonic::<tables::PlainStorageState>().unwrap(),
+            Some((second, second))
+        );
+    }
+
+    #[test]
+    fn diff_tables_of_two_databases() {
+        let db = create_test_rw_db();
+        insert_canonical_headers(&*db, 5);
+        let other_db = create_test_rw_db();
+        other_db
+            .update(|tx| {
+                for number in 1..6 {
+                    let hash = if number == 3 {
+                        H256::repeat_byte(0xff)
+                    } else {
+                        H256::from_low_u64_be(number)
+                    };
+                    tx.put::<tables::CanonicalHeaders>(number, hash)?;
+                }
+                Ok::<_, DatabaseError>(())
+            })
+            .unwrap()
+            .unwrap();
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+        let other = DbTool::new(&*other_db, MAINNET.clone()).unwrap();
+
+        let diff = tool.diff_table::<tables::CanonicalHeaders, _>(&other).unwrap();
+        assert_eq!(diff.only_in_self, vec![0]);
+        assert_eq!(diff.only_in_other, vec![5]);
+        assert_eq!(diff.changed, vec![3]);
+
+        let mut same = DbTool::new(&*other_db, MAINNET.clone()).unwrap();
+        let diff = same.diff_table::<tables::CanonicalHeaders, _>(&other).unwrap();
+        assert!(diff.is_empty(), "{diff:?}");
+    }
+
+    #[test]
+    fn list_dup_values() {
+        let db = create_test_rw_db();
+        let (first, second) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let entry =
+            |slot| StorageEntry { key: H256::from_low_u64_be(slot), value: U256::from(slot) };
+        db.update(|tx| {
+            for (address, slot) in [(first, 2), (first, 1), (second, 1), (first, 3)] {
+                tx.put::<tables::PlainStorageState>(address, entry(slot))?;
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+
+        assert_eq!(
+            tool.list_dups::<tables::PlainStorageState>(first).unwrap(),
+            vec![entry(1), entry(2), entry(3)]
+        );
+        assert_eq!(tool.list_dups::<tables::PlainStorageState>(second).unwrap(), vec![entry(1)]);
+        assert!(tool
+            .list_dups::<tables::PlainStorageState>(Address::from_low_u64_be(3))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn read_tables_in_single_tx() {
+        let db = create_test_rw_db();
+        insert_canonical_headers(&*db, 3);
+        db.update(|tx| tx.put::<tables::Headers>(2, Header { number: 2, ..Default::default() }))
+            .unwrap()
+            .unwrap();
+        let tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+
+        let (hash, header) = tool
+            .with_read_tx(|tx| {
+                let hash = tx.get::<tables::CanonicalHeaders>(2)?;
+                let header = tx.get::<tables::Headers>(2)?;
+                Ok((hash, header))
+            })
+            .unwrap();
+        assert_eq!(hash, Some(H256::from_low_u64_be(2)));
+        assert_eq!(header.map(|header| header.number), Some(2));
+    }
+
+    #[test]
+    fn get_many_keys() {
+        let db = create_test_rw_db();
+        insert_canonical_headers(&*db, 3);
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+
+        let entries = tool.get_many::<tables::CanonicalHeaders>(vec![2, 7, 0, 3]).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                (2, Some(H256::from_low_u64_be(2))),
+                (7, None),
+                (0, Some(H256::from_low_u64_be(0))),
+                (3, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn count_entries() {
+        let db = create_test_rw_db();
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+        assert_eq!(tool.count::<tables::CanonicalHeaders>().unwrap(), 0);
+
+        insert_canonical_headers(&*db, 7);
+        assert_eq!(tool.count::<tables::CanonicalHeaders>().unwrap(), 7);
+        assert_eq!(tool.count::<tables::Headers>().unwrap(), 0);
+    }
+
+    #[test]
+    fn value_size_histogram() {
+        let db = create_test_rw_db();
+        db.update(|tx| {
+            // a header without extra data encodes to less than 1024 bytes
+            for (number, extra_data) in [(0, 0), (1, 0), (2, 1_000), (3, 5_000)] {
+                let header =
+                    Header { number, extra_data: vec![1; extra_data].into(), ..Default::default() };
+                tx.put::<tables::Headers>(number, header)?;
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+
+        assert_eq!(
+            tool.value_size_histogram::<tables::Headers>(&[1024, 4096]).unwrap(),
+            vec![(1024, 2), (4096, 1), (usize::MAX, 1)]
+        );
+        assert_eq!(
+            tool.value_size_histogram::<tables::CanonicalHeaders>(&[32]).unwrap(),
+            vec![(32, 0), (usize::MAX, 0)]
+        );
+    }
+
+    #[test]
+    fn list_range() {
+        let db = create_test_rw_db();
+        insert_canonical_headers(&*db, 10);
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+
+        let keys = |entries: Vec<(BlockNumber, H256)>| {
+            entries.into_iter().map(|(number, _)| number).collect::<Vec<_>>()
+        };
+        let range = tool.list_range::<tables::CanonicalHeaders>(3, 6, false, false).unwrap();
+        assert_eq!(keys(range), vec![3, 4, 5]);
+        let range = tool.list_range::<tables::CanonicalHeaders>(3, 6, true, false).unwrap();
+        assert_eq!(range[3], (6, H256::from_low_u64_be(6)));
+        let range = tool.list_range::<tables::CanonicalHeaders>(3, 6, true, true).unwrap();
+        assert_eq!(keys(range), vec![6, 5, 4, 3]);
+        let range = tool.list_range::<tables::CanonicalHeaders>(8, 20, false, true).unwrap();
+        assert_eq!(keys(range), vec![9, 8]);
+    }
+
+    #[test]
+    fn verify_header_hashes() {
+        let db = create_test_rw_db();
+        db.update(|tx| {
+            for number in 0..5 {
+                let header = Header { number, ..Default::default() }.seal_slow();
+                tx.put::<tables::Headers>(number, header.clone().unseal())?;
+                // block 3 points to a bogus canonical hash
+                let hash = if number == 3 { H256::zero() } else { header.hash() };
+                tx.put::<tables::CanonicalHeaders>(number, hash)?;
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+        assert_eq!(tool.verify_header_hashes(..).unwrap(), vec![3]);
+        assert_eq!(tool.verify_header_hashes(0..3).unwrap(), Vec::<BlockNumber>::new());
+    }
+
+    #[test]
+    fn unwind_canonical_headers() {
+        let db = create_test_rw_db();
+        insert_canonical_headers(&*db, 10);
+
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+        assert_eq!(tool.unwind_canonical_to(u64::MAX, true).unwrap(), 0);
+        assert_eq!(tool.unwind_canonical_to(6, true).unwrap(), 3);
+        assert_eq!(tool.unwind_canonical_to(6, true).unwrap(), 0);
+
+        let remaining = tool.list::<tables::CanonicalHeaders>(0, usize::MAX, false).unwrap();
+        assert_eq!(
+            remaining.into_iter().map(|(number, _)| number).collect::<Vec<_>>(),
+            (0..=6).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn unwind_canonical_headers_requires_dangerous() {
+        let db = create_test_rw_db();
+        insert_canonical_headers(&*db, 10);
+
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+        assert!(matches!(
+            tool.unwind_canonical_to(6, false),
+            Err(DbToolError::DangerousNotEnabled("unwind_canonical_to"))
+        ));
+        assert_eq!(tool.list::<tables::CanonicalHeaders>(0, usize::MAX, false).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn prune_table_outside_range() {
+        let db = create_test_rw_db();
+        insert_canonical_headers(&*db, PRUNE_BATCH_SIZE as u64 + 10);
+
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+        let keep = 5..=PRUNE_BATCH_SIZE as u64 + 2;
+        let deleted =
+            tool.prune_outside::<tables::CanonicalHeaders>(keep.clone(), |n| *n, true).unwrap();
+        assert_eq!(deleted, 5 + 7);
+
+        let remaining = tool.list::<tables::CanonicalHeaders>(0, usize::MAX, false).unwrap();
+        assert_eq!(
+            remaining.into_iter().map(|(number, _)| number).collect::<Vec<_>>(),
+            keep.collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn prune_dupsort_table_outside_range() {
+        let db = create_test_rw_db();
+        let entry =
+            |slot| StorageEntry { key: H256::from_low_u64_be(slot), value: U256::from(slot) };
+        // more duplicates under the kept key than fit into a single batch
+        let kept_slots = PRUNE_BATCH_SIZE as u64 + 5;
+        db.update(|tx| {
+            for slot in 1..=3 {
+                tx.put::<tables::PlainStorageState>(Address::from_low_u64_be(1), entry(slot))?;
+                tx.put::<tables::PlainStorageState>(Address::from_low_u64_be(3), entry(slot))?;
+            }
+            for slot in 1..=kept_slots {
+                tx.put::<tables::PlainStorageState>(Address::from_low_u64_be(2), entry(slot))?;
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+        let deleted = tool
+            .prune_outside::<tables::PlainStorageState>(
+                2..=2,
+                |address| address.to_low_u64_be(),
+                true,
+            )
+            .unwrap();
+        assert_eq!(deleted, 6);
+
+        let remaining = tool.list::<tables::PlainStorageState>(0, usize::MAX, false).unwrap();
+        assert_eq!(remaining.len(), kept_slots as usize);
+        assert!(remaining.iter().all(|(address, _)| *address == Address::from_low_u64_be(2)));
+    }
+
+    #[test]
+    fn prune_outside_requires_dangerous() {
+        let db = create_test_rw_db();
+        insert_canonical_headers(&*db, 10);
+
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+        assert!(matches!(
+            tool.prune_outside::<tables::CanonicalHeaders>(2..=5, |n| *n, false),
+            Err(DbToolError::DangerousNotEnabled("prune_outside"))
+        ));
+        assert_eq!(tool.list::<tables::CanonicalHeaders>(0, usize::MAX, false).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn verify_block_receipts_reports_tampered_receipt() {
+        let executed = (0..3).map(receipt).collect::<Vec<_>>();
+        let tampered = [
+            Receipt { cumulative_gas_used: 40_000, ..executed[1].clone() },
+            Receipt { logs: Vec::new(), ..executed[1].clone() },
+            Receipt { success: false, ..executed[1].clone() },
+        ];
+
+        for tampered in tampered {
+            let db = create_test_rw_db();
+            let mut stored = executed.clone();
+            stored[1] = tampered.clone();
+            insert_block_with_receipts(&*db, &stored);
+
+            let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+            let mismatches =
+                tool.verify_block_receipts(1, &executor_with_receipts(&executed)).unwrap();
+            assert_eq!(
+                mismatches,
+                vec![ReceiptMismatch {
+                    index: 1,
+                    stored: Some(tampered),
+                    executed: Some(executed[1].clone()),
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn verify_block_receipts_matching() {
+        let receipts = (0..3).map(receipt).collect::<Vec<_>>();
+        let db = create_test_rw_db();
+        insert_block_with_receipts(&*db, &receipts);
+
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+        let mismatches = tool.verify_block_receipts(1, &executor_with_receipts(&receipts)).unwrap();
+        assert!(mismatches.is_empty());
+    }
+
+    #[test]
+    fn recent_blocks_newest_first() {
+        let db = create_test_rw_db();
+        let blocks = random_block_range(0..=4, H256::zero(), 0..3);
+        db.update(|tx| {
+            for block in blocks.iter().cloned() {
+                insert_canonical_block(tx, block, None)?;
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+        assert_eq!(
+            tool.recent_blocks(3).unwrap(),
+            blocks[2..].iter().rev().cloned().collect::<Vec<_>>()
+        );
+        // the chain is shorter than the requested number of blocks
+        assert_eq!(
+            tool.recent_blocks(10).unwrap(),
+            blocks.iter().rev().cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn recent_blocks_empty_db() {
+        let db = create_test_rw_db();
+        let mut tool = DbTool::new(&*db, MAINNET.clone()).unwrap();
+        assert!(tool.recent_blocks(3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn table_dispatch_covers_all_tables() {
+        for (_, name) in tables::TABLES {
+            assert_eq!(table_dispatch!(name, T => T::NAME, _ => "unknown"), name);
+        }
+        assert_eq!(table_dispatch!("Unknown", T => T::NAME, _ => "unknown"), "unknown");
+    }
+}
@@ -95,6 +95,8 @@ pub enum ConsensusError {
     BaseFeeMissing,
     #[error("Block base fee ({got}) is different than expected: ({expected}).")]
     BaseFeeDiff { expected: u64, got: u64 },
+    #[error("Unexpected base fee before London.")]
+    BaseFeeUnexpected,
     #[error("Transaction signer recovery error.")]
     TransactionSignerRecoveryError,
     #[error("Extra data {len} exceeds max length: ")]
@@ -1,7 +1,7 @@
 use super::headers::client::HeadersRequest;
 use crate::{consensus, db};
 use reth_network_api::ReputationChangeKind;
-use reth_primitives::{BlockHashOrNumber, BlockNumber, Header, WithPeerId, H256};
+use reth_primitives::{BlockHashOrNumber, BlockNumber, Header, PeerId, WithPeerId, H256};
 use std::ops::RangeInclusive;
 use thiserror::Error;
 use tokio::sync::{mpsc, oneshot};
@@ -62,6 +62,7 @@ impl EthResponseValidator for RequestResult<Vec<Header>> {
                 RequestError::UnsupportedCapability => None,
                 RequestError::Timeout => Some(ReputationChangeKind::Timeout),
                 RequestError::BadResponse => None,
+                RequestError::NetworkDisabled => None,
             }
         } else {
             None
@@ -83,6 +84,8 @@ pub enum RequestError {
     Timeout,
     #[error("Received bad response.")]
     BadResponse,
+    #[error("Network is disabled.")]
+    NetworkDisabled,
 }
 
 // === impl RequestError ===
@@ -159,6 +162,20 @@ pub enum DownloadError {
         /// How many headers we expected.
         expected: u64,
     },
+    /// Received more headers than requested.
+    #[error("Received more headers than requested. Expected: {expected}. Received: {received}")]
+    TooManyHeaders {
+        /// How many headers we received.
+        received: usize,
+        /// How many headers we expected.
+        expected: usize,
+    },
+    /// Downloaded headers do not form a contiguous chain.
+    #[error("Downloaded headers are not contiguous at index {index}.")]
+    HeaderChainBroken {
+        /// The index of the first header that does not attach to the previous one.
+        index: usize,
+    },
     /* ==================== BODIES ERRORS ==================== */
     /// Block validation failed
     #[error("Failed to validate body for header {hash}. Details: {error}.")]
@@ -196,6 +213,14 @@ pub enum DownloadError {
     /// Received empty response while expecting non empty
     #[error("Received empty response.")]
     EmptyResponse,
+    /// The error was caused by the response of the given peer.
+    #[error("Invalid response from peer {peer_id}: {error}")]
+    FromPeer {
+        /// The peer that sent the invalid response.
+        peer_id: PeerId,
+        /// The error caused by the response.
+        error: Box<DownloadError>,
+    },
     /// Error while executing the request.
     #[error(transparent)]
     RequestError(#[from] RequestError),
@@ -204,6 +229,26 @@ pub enum DownloadError {
     DatabaseError(#[from] db::DatabaseError),
 }
 
+// === impl DownloadError ===
+
+impl DownloadError {
+    /// Attributes the error to the given peer, see [DownloadError::FromPeer].
+    pub fn with_peer(self, peer_id: PeerId) -> Self {
+        match self {
+            DownloadError::FromPeer { error, .. } => DownloadError::FromPeer { peer_id, error },
+            error => DownloadError::FromPeer { peer_id, error: Box::new(error) },
+        }
+    }
+
+    /// Returns the peer the error is attributed to, if any.
+    pub fn peer_id(&self) -> Option<PeerId> {
+        match self {
+            DownloadError::FromPeer { peer_id, .. } => Some(*peer_id),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -220,4 +265,17 @@ mod tests {
         let headers: Vec<Header> = vec![];
         assert!(Ok(headers).is_likely_bad_headers_response(&request));
     }
+
+    #[test]
+    fn attribute_error_to_peer() {
+        let peer_id = PeerId::random();
+        assert_eq!(DownloadError::EmptyResponse.peer_id(), None);
+
+        let error = DownloadError::EmptyResponse.with_peer(PeerId::random()).with_peer(peer_id);
+        assert_eq!(error.peer_id(), Some(peer_id));
+        assert_eq!(
+            error,
+            DownloadError::FromPeer { peer_id, error: Box::new(DownloadError::EmptyResponse) }
+        );
+    }
 }
@@ -1,8 +1,12 @@
-use crate::p2p::{download::DownloadClient, error::PeerRequestResult, priority::Priority};
+use crate::p2p::{
+    download::DownloadClient, error::PeerRequestResult, headers::error::HeadersRequestError,
+    priority::Priority,
+};
 use futures::{Future, FutureExt};
 pub use reth_eth_wire::BlockHeaders;
-use reth_primitives::{BlockHashOrNumber, Header, HeadersDirection};
+use reth_primitives::{BlockHashOrNumber, Header, HeadersDirection, PeerId, SealedHeader};
 use std::{
+    collections::HashSet,
     fmt::Debug,
     pin::Pin,
     task::{ready, Context, Poll},
@@ -20,6 +24,112 @@ pub struct HeadersRequest {
     pub direction: HeadersDirection,
 }
 
+impl HeadersRequest {
+    /// Returns a [HeadersRequestBuilder] that validates the request.
+    pub fn builder() -> HeadersRequestBuilder {
+        HeadersRequestBuilder::default()
+    }
+}
+
+/// The default maximum limit of a [HeadersRequest] built with a [HeadersRequestBuilder].
+///
+/// This is the maximum number of headers peers serve per request.
+pub const DEFAULT_MAX_HEADERS_LIMIT: u64 = 1024;
+
+/// Builds a [HeadersRequest] with a validated limit.
+///
+/// The direction defaults to [HeadersDirection::Rising] and the limit to `1`.
+#[derive(Clone, Debug)]
+pub struct HeadersRequestBuilder {
+    start: Option<BlockHashOrNumber>,
+    limit: u64,
+    direction: HeadersDirection,
+    max_limit: u64,
+}
+
+impl Default for HeadersRequestBuilder {
+    fn default() -> Self {
+        Self {
+            start: None,
+            limit: 1,
+            direction: HeadersDirection::Rising,
+            max_limit: DEFAULT_MAX_HEADERS_LIMIT,
+        }
+    }
+}
+
+impl HeadersRequestBuilder {
+    /// Sets the starting block.
+    pub fn start(mut self, start: impl Into<BlockHashOrNumber>) -> Self {
+        self.start = Some(start.into());
+        self
+    }
+
+    /// Sets the maximum number of requested headers.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Sets the direction in which headers should be returned.
+    pub fn direction(mut self, direction: HeadersDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Sets the maximum allowed limit, defaults to [DEFAULT_MAX_HEADERS_LIMIT].
+    pub fn max_limit(mut self, max_limit: u64) -> Self {
+        self.max_limit = max_limit;
+        self
+    }
+
+    /// Validates the configured values and returns the [HeadersRequest].
+    pub fn build(self) -> Result<HeadersRequest, HeadersRequestError> {
+        let start = self.start.ok_or(HeadersRequestError::MissingStart)?;
+        if self.limit == 0 {
+            return Err(HeadersRequestError::ZeroLimit)
+        }
+        if self.limit > self.max_limit {
+            return Err(HeadersRequestError::LimitTooLarge {
+                limit: self.limit,
+                max: self.max_limit,
+            })
+        }
+        Ok(HeadersRequest { start, limit: self.limit, direction: self.direction })
+    }
+}
+
+/// The headers of a response to a [HeadersRequest], in the order the peer sent them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HeadersResponse(pub Vec<Header>);
+
+impl HeadersResponse {
+    /// Seals the headers and sorts them by block number in the given direction, keeping only the
+    /// first header of each hash.
+    pub fn sorted(self, direction: HeadersDirection) -> Vec<SealedHeader> {
+        let mut seen = HashSet::with_capacity(self.0.len());
+        let mut headers = self
+            .0
+            .into_iter()
+            .map(Header::seal_slow)
+            .filter(|header| seen.insert(header.hash()))
+            .collect::<Vec<_>>();
+        match direction {
+            HeadersDirection::Rising => headers.sort_by_key(|header| header.number),
+            HeadersDirection::Falling => {
+                headers.sort_by_key(|header| std::cmp::Reverse(header.number))
+            }
+        }
+        headers
+    }
+}
+
+impl From<Vec<Header>> for HeadersResponse {
+    fn from(headers: Vec<Header>) -> Self {
+        HeadersResponse(headers)
+    }
+}
+
 /// The headers future type
 pub type HeadersFut = Pin<Box<dyn Future<Output = PeerRequestResult<Vec<Header>>> + Send + Sync>>;
 
@@ -43,6 +153,12 @@ pub trait HeadersClient: DownloadClient {
         priority: Priority,
     ) -> Self::Output;
 
+    /// Rewards the peer for responding with a message that passed validation.
+    ///
+    /// This is the counterpart of [DownloadClient::report_bad_message] and does nothing by
+    /// default.
+    fn report_good_message(&self, _peer_id: PeerId) {}
+
     /// Fetches a single header for the requested number or hash.
     fn get_header(&self, start: BlockHashOrNumber) -> SingleHeaderRequest<Self::Output> {
         self.get_header_with_priority(start, Priority::Normal)
@@ -84,3 +200,50 @@ where
         Poll::Ready(resp)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_headers_request() {
+        let request = HeadersRequest::builder().start(10u64).limit(5).build().unwrap();
+        assert_eq!(request.start, BlockHashOrNumber::Number(10));
+        assert_eq!(request.limit, 5);
+        assert_eq!(request.direction, HeadersDirection::Rising);
+
+        let request =
+            HeadersRequest::builder().start(10u64).direction(HeadersDirection::Falling).build();
+        assert_eq!(request.unwrap().direction, HeadersDirection::Falling);
+    }
+
+    #[test]
+    fn sort_headers_response() {
+        let headers = [3, 1, 2, 3, 0].map(|number| Header { number, ..Default::default() });
+        let response = HeadersResponse::from(headers.to_vec());
+
+        let numbers = |headers: Vec<SealedHeader>| {
+            headers.into_iter().map(|header| header.number).collect::<Vec<_>>()
+        };
+        let rising = response.clone().sorted(HeadersDirection::Rising);
+        assert_eq!(rising[0], headers[4].clone().seal_slow());
+        assert_eq!(numbers(rising), vec![0, 1, 2, 3]);
+        assert_eq!(numbers(response.sorted(HeadersDirection::Falling)), vec![3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn reject_invalid_headers_request() {
+        assert_eq!(
+            HeadersRequest::builder().start(1u64).limit(0).build().unwrap_err(),
+            HeadersRequestError::ZeroLimit
+        );
+        assert_eq!(
+            HeadersRequest::builder().start(1u64).limit(11).max_limit(10).build().unwrap_err(),
+            HeadersRequestError::LimitTooLarge { limit: 11, max: 10 }
+        );
+        assert_eq!(
+            HeadersRequest::builder().limit(1).build().unwrap_err(),
+            HeadersRequestError::MissingStart
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use super::error::HeadersDownloaderResult;
+use super::{client::DEFAULT_MAX_HEADERS_LIMIT, error::HeadersDownloaderResult};
 use crate::{
     consensus::Consensus,
     p2p::error::{DownloadError, DownloadResult},
@@ -32,6 +32,13 @@ pub trait HeaderDownloader:
 
     /// Sets the headers batch size that the Stream should return.
     fn set_batch_size(&mut self, limit: usize);
+
+    /// Returns the maximum number of headers accepted in a single response.
+    ///
+    /// Larger responses are rejected and the peer that sent them is reported.
+    fn max_response_len(&self) -> usize {
+        DEFAULT_MAX_HEADERS_LIMIT as usize
+    }
 }
 
 /// Specifies the target to sync for [HeaderDownloader::update_sync_target]
@@ -89,3 +96,72 @@ pub fn validate_header_download(
         .map_err(|error| DownloadError::HeaderValidation { hash: parent.hash(), error })?;
     Ok(())
 }
+
+/// Validates that the headers form a contiguous chain, in either rising or falling order.
+///
+/// Every header must have a block number one above (rising) or below (falling) the previous
+/// header, and the higher header of each pair must link to the lower one by its parent hash. The
+/// direction is determined by the first two headers.
+///
+/// Returns [DownloadError::HeaderChainBroken] with the index of the first header that does not
+/// attach to the previous one.
+pub fn validate_header_chain(headers: &[SealedHeader]) -> DownloadResult<()> {
+    let rising = match headers {
+        [first, second, ..] => second.number > first.number,
+        _ => return Ok(()),
+    };
+    for (index, pair) in headers.windows(2).enumerate() {
+        let (parent, child) = if rising { (&pair[0], &pair[1]) } else { (&pair[1], &pair[0]) };
+        if parent.number.checked_add(1) != Some(child.number) || child.parent_hash != parent.hash()
+        {
+            return Err(DownloadError::HeaderChainBroken { index: index + 1 })
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::generators::random_header_range;
+
+    #[test]
+    fn validate_contiguous_header_chain() {
+        let headers = random_header_range(0..10, H256::zero());
+        assert_eq!(validate_header_chain(&headers), Ok(()));
+        assert_eq!(validate_header_chain(&headers[..1]), Ok(()));
+        assert_eq!(validate_header_chain(&[]), Ok(()));
+
+        let mut falling = headers.clone();
+        falling.reverse();
+        assert_eq!(validate_header_chain(&falling), Ok(()));
+    }
+
+    #[test]
+    fn reject_broken_parent_link() {
+        let mut headers = random_header_range(0..10, H256::zero());
+        let mut header = headers[4].clone().unseal();
+        header.parent_hash = H256::random();
+        headers[4] = header.seal_slow();
+        assert_eq!(
+            validate_header_chain(&headers),
+            Err(DownloadError::HeaderChainBroken { index: 4 })
+        );
+
+        headers.reverse();
+        assert_eq!(
+            validate_header_chain(&headers),
+            Err(DownloadError::HeaderChainBroken { index: 6 })
+        );
+    }
+
+    #[test]
+    fn reject_number_gap() {
+        let mut headers = random_header_range(0..10, H256::zero());
+        headers.remove(6);
+        assert_eq!(
+            validate_header_chain(&headers),
+            Err(DownloadError::HeaderChainBroken { index: 6 })
+        );
+    }
+}
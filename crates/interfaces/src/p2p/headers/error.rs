@@ -20,3 +20,23 @@ pub enum HeadersDownloaderError {
         error: Box<ConsensusError>,
     },
 }
+
+/// Error variants that can happen when building a
+/// [HeadersRequest](crate::p2p::headers::client::HeadersRequest).
+#[derive(Debug, Error, Clone, Eq, PartialEq)]
+pub enum HeadersRequestError {
+    /// No start block was set.
+    #[error("Missing start block.")]
+    MissingStart,
+    /// The request would not return any headers.
+    #[error("Limit must not be zero.")]
+    ZeroLimit,
+    /// The limit exceeds the maximum.
+    #[error("Limit {limit} exceeds the maximum of {max}.")]
+    LimitTooLarge {
+        /// The requested limit.
+        limit: u64,
+        /// The maximum allowed limit.
+        max: u64,
+    },
+}
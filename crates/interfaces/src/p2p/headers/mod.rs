@@ -12,3 +12,6 @@ pub mod downloader;
 
 /// Header downloader error.
 pub mod error;
+
+/// Pairs header responses with the requests they answer.
+pub mod tracker;
@@ -0,0 +1,188 @@
+use crate::p2p::error::{RequestError, RequestResult};
+use futures::{Future, Stream, StreamExt};
+use parking_lot::Mutex;
+use reth_eth_wire::{message::RequestPair, BlockHeaders};
+use reth_primitives::Header;
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tokio::sync::oneshot;
+
+/// A stream of header responses, each tagged with the id of the request it answers.
+pub trait HeadersStream: Stream<Item = RequestPair<BlockHeaders>> + Unpin + Send {}
+
+impl<S> HeadersStream for S where S: Stream<Item = RequestPair<BlockHeaders>> + Unpin + Send {}
+
+/// The senders of the pending response futures, keyed by request id, or `None` once the tracker
+/// completed.
+type PendingResponses = Arc<Mutex<Option<HashMap<u64, oneshot::Sender<Vec<Header>>>>>>;
+
+/// Pairs the responses of a shared [HeadersStream] with the requests they answer, by request id.
+///
+/// The tracker is a future that drives the stream and must be polled, e.g. spawned, for the
+/// futures returned by [HeaderRequestHandle::track] to resolve. Responses to untracked requests
+/// are dropped. Once the stream ends, the tracker completes and all pending and future requests
+/// fail with [RequestError::ChannelClosed].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct HeaderRequestTracker<S> {
+    stream: S,
+    pending: PendingResponses,
+    timeout: Duration,
+}
+
+impl<S: HeadersStream> HeaderRequestTracker<S> {
+    /// Creates a tracker for the given stream, whose requests time out after `timeout`.
+    ///
+    /// This should be the request timeout of the downloader that sends the requests.
+    pub fn new(stream: S, timeout: Duration) -> Self {
+        Self { stream, pending: Arc::new(Mutex::new(Some(HashMap::new()))), timeout }
+    }
+
+    /// Returns a handle for tracking requests, which can be used after the tracker is spawned.
+    pub fn handle(&self) -> HeaderRequestHandle {
+        HeaderRequestHandle { pending: self.pending.clone(), timeout: self.timeout }
+    }
+}
+
+impl<S: HeadersStream> Future for HeaderRequestTracker<S> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        while let Some(RequestPair { request_id, message }) =
+            ready!(this.stream.poll_next_unpin(cx))
+        {
+            let tx = this.pending.lock().as_mut().and_then(|pending| pending.remove(&request_id));
+            if let Some(tx) = tx {
+                // the receiver may have timed out already
+                let _ = tx.send(message.0);
+            }
+        }
+        // dropping the senders fails all pending requests
+        this.pending.lock().take();
+        Poll::Ready(())
+    }
+}
+
+/// A handle for tracking requests of a [HeaderRequestTracker].
+#[derive(Debug, Clone)]
+pub struct HeaderRequestHandle {
+    pending: PendingResponses,
+    timeout: Duration,
+}
+
+impl HeaderRequestHandle {
+    /// Tracks the request with the given id and returns a future that resolves to its response.
+    ///
+    /// The request is tracked immediately, so a response that arrives before the future is polled
+    /// is not missed. The future fails with [RequestError::Timeout] if no response arrives within
+    /// the timeout of the tracker, and with [RequestError::ChannelClosed] right away if the tracker
+    /// already completed. The request is no longer tracked once the future is dropped.
+    pub fn track(
+        &self,
+        request_id: u64,
+    ) -> impl Future<Output = RequestResult<Vec<Header>>> + Send + 'static {
+        let (tx, rx) = oneshot::channel();
+        // if the tracker completed, the sender is dropped here and the receiver fails immediately
+        if let Some(pending) = self.pending.lock().as_mut() {
+            pending.insert(request_id, tx);
+        }
+        let guard = PendingGuard { pending: self.pending.clone(), request_id };
+        let timeout = self.timeout;
+        async move {
+            let _guard = guard;
+            match tokio::time::timeout(timeout, rx).await {
+                Ok(Ok(headers)) => Ok(headers),
+                Ok(Err(_)) => Err(RequestError::ChannelClosed),
+                Err(_) => Err(RequestError::Timeout),
+            }
+        }
+    }
+}
+
+/// Stops tracking a request when its response future is dropped.
+#[derive(Debug)]
+struct PendingGuard {
+    pending: PendingResponses,
+    request_id: u64,
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        if let Some(pending) = self.pending.lock().as_mut() {
+            pending.remove(&self.request_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::mpsc;
+
+    #[tokio::test]
+    async fn pair_concurrent_requests_by_id() {
+        let (tx, rx) = mpsc::unbounded();
+        let tracker = HeaderRequestTracker::new(rx, Duration::from_secs(5));
+        let handle = tracker.handle();
+        tokio::spawn(tracker);
+
+        let first = handle.track(1);
+        let second = handle.track(2);
+        let headers = |number| vec![Header { number, ..Default::default() }];
+        // respond in reverse order, with a response to an untracked request in between
+        for (request_id, number) in [(2, 20), (3, 30), (1, 10)] {
+            tx.unbounded_send(RequestPair { request_id, message: BlockHeaders(headers(number)) })
+                .unwrap();
+        }
+
+        let (first, second) = futures::join!(first, second);
+        assert_eq!(first, Ok(headers(10)));
+        assert_eq!(second, Ok(headers(20)));
+
+        let pending = handle.track(4);
+        drop(tx);
+        assert_eq!(pending.await, Err(RequestError::ChannelClosed));
+    }
+
+    #[tokio::test]
+    async fn request_without_response_times_out() {
+        let (_tx, rx) = mpsc::unbounded::<RequestPair<BlockHeaders>>();
+        let tracker = HeaderRequestTracker::new(rx, Duration::from_millis(10));
+        let handle = tracker.handle();
+        tokio::spawn(tracker);
+
+        assert_eq!(handle.track(1).await, Err(RequestError::Timeout));
+        assert_eq!(handle.pending.lock().as_ref().map(HashMap::len), Some(0));
+    }
+
+    #[tokio::test]
+    async fn dropped_request_is_untracked() {
+        let (_tx, rx) = mpsc::unbounded::<RequestPair<BlockHeaders>>();
+        let tracker = HeaderRequestTracker::new(rx, Duration::from_secs(5));
+        let handle = tracker.handle();
+
+        let request = handle.track(1);
+        assert_eq!(handle.pending.lock().as_ref().map(HashMap::len), Some(1));
+        drop(request);
+        assert_eq!(handle.pending.lock().as_ref().map(HashMap::len), Some(0));
+    }
+
+    #[tokio::test]
+    async fn track_after_close_fails_fast() {
+        let (tx, rx) = mpsc::unbounded::<RequestPair<BlockHeaders>>();
+        let tracker = HeaderRequestTracker::new(rx, Duration::from_secs(5));
+        let handle = tracker.handle();
+        drop(tx);
+        tracker.await;
+
+        let res = tokio::time::timeout(Duration::from_secs(1), handle.track(1)).await;
+        assert_eq!(res, Ok(Err(RequestError::ChannelClosed)));
+        assert!(handle.pending.lock().is_none());
+    }
+}
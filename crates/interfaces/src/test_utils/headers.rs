@@ -11,8 +11,10 @@ use crate::{
         },
         priority::Priority,
     },
+    sync::{NetworkSyncUpdater, SyncState},
 };
 use futures::{future, Future, FutureExt, Stream, StreamExt};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use reth_eth_wire::BlockHeaders;
 use reth_primitives::{
     BlockHash, BlockNumber, Head, Header, HeadersDirection, PeerId, SealedBlock, SealedHeader,
@@ -20,6 +22,7 @@ use reth_primitives::{
 };
 use reth_rpc_types::engine::ForkchoiceState;
 use std::{
+    collections::VecDeque,
     fmt,
     pin::Pin,
     sync::{
@@ -27,6 +30,7 @@ use std::{
         Arc,
     },
     task::{ready, Context, Poll},
+    time::Duration,
 };
 use tokio::sync::{
     oneshot::{error::RecvError, Receiver},
@@ -44,6 +48,8 @@ pub struct TestHeaderDownloader {
     download: Option<TestDownload>,
     queued_headers: Vec<SealedHeader>,
     batch_size: usize,
+    /// Canned headers that are returned instead of downloading from the client.
+    canned_headers: Option<Vec<SealedHeader>>,
 }
 
 impl TestHeaderDownloader {
@@ -54,7 +60,34 @@ impl TestHeaderDownloader {
         limit: u64,
         batch_size: usize,
     ) -> Self {
-        Self { client, consensus, limit, download: None, batch_size, queued_headers: Vec::new() }
+        Self {
+            client,
+            consensus,
+            limit,
+            download: None,
+            batch_size,
+            queued_headers: Vec::new(),
+            canned_headers: None,
+        }
+    }
+
+    /// Instantiates a downloader that returns the given headers, except for the headers at the
+    /// `drop_at` indices.
+    ///
+    /// The remaining headers are returned unmodified, so the header after a gap still links to
+    /// the parent hash of the omitted header, which allows to exercise the gap detection.
+    pub fn with_gaps(headers: Vec<SealedHeader>, drop_at: Vec<usize>) -> Self {
+        let len = headers.len();
+        let headers = headers
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, _)| !drop_at.contains(idx))
+            .map(|(_, header)| header)
+            .collect();
+        let mut downloader =
+            Self::new(TestHeadersClient::default(), Default::default(), len as u64, len.max(1));
+        downloader.canned_headers = Some(headers);
+        downloader
     }
 
     fn create_download(&self) -> TestDownload {
@@ -89,6 +122,14 @@ impl Stream for TestHeaderDownloader {
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
+        if let Some(headers) = &mut this.canned_headers {
+            if headers.is_empty() {
+                return Poll::Ready(None)
+            }
+            let batch = headers.drain(..this.batch_size.min(headers.len())).collect();
+            return Poll::Ready(Some(Ok(batch)))
+        }
+
         loop {
             if this.queued_headers.len() == this.batch_size {
                 return Poll::Ready(Some(Ok(std::mem::take(&mut this.queued_headers))))
@@ -193,20 +234,113 @@ pub struct TestHeadersClient {
     responses: Arc<Mutex<Vec<Header>>>,
     error: Arc<Mutex<Option<RequestError>>>,
     request_attempts: Arc<AtomicU64>,
+    corruption: Arc<Mutex<Option<ResponseCorruption>>>,
+    response_delay: Arc<Mutex<Option<Duration>>>,
+    /// The timeout after which requests fail with [RequestError::Timeout], if any.
+    request_timeout: Arc<Mutex<Option<Duration>>>,
+    timed_out_requests: Arc<AtomicU64>,
+    drop_next_response: Arc<AtomicBool>,
+    request_log: Arc<parking_lot::Mutex<Vec<HeadersRequest>>>,
+    good_messages: Arc<AtomicU64>,
+    /// The `(height, hash, td)` of the latest status update.
+    last_status: Arc<parking_lot::Mutex<Option<(BlockNumber, H256, U256)>>>,
+    /// The maximum number of queued headers, unbounded if `None`.
+    capacity: Option<usize>,
+    /// The peer all responses are attributed to.
+    peer_id: PeerId,
+}
+
+/// Deterministically corrupts a fraction of the responses of a [TestHeadersClient].
+#[derive(Debug)]
+struct ResponseCorruption {
+    /// The probability that a response is corrupted.
+    rate: f64,
+    /// The seeded rng deciding which responses are corrupted and how.
+    rng: StdRng,
+}
+
+impl ResponseCorruption {
+    /// Replaces the parent hash of a random header in the response with probability `rate`.
+    fn maybe_corrupt(&mut self, headers: &mut [Header]) {
+        if headers.is_empty() || !self.rng.gen_bool(self.rate) {
+            return
+        }
+        let idx = self.rng.gen_range(0..headers.len());
+        headers[idx].parent_hash = H256(self.rng.gen());
+    }
 }
 
 impl TestHeadersClient {
+    /// Creates a client that queues at most `capacity` headers that were sent via
+    /// [TestHeadersClient::send_header_response].
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { capacity: Some(capacity), ..Default::default() }
+    }
+
+    /// Attributes all responses to the given peer instead of the default peer id.
+    pub fn with_peer_id(mut self, peer_id: PeerId) -> Self {
+        self.peer_id = peer_id;
+        self
+    }
+
     /// Return the number of times client was polled
     pub fn request_attempts(&self) -> u64 {
         self.request_attempts.load(Ordering::SeqCst)
     }
 
+    /// Returns the number of times a peer was reported for a good message.
+    pub fn good_messages(&self) -> u64 {
+        self.good_messages.load(Ordering::SeqCst)
+    }
+
+    /// Returns the `(height, hash, td)` of the latest status announced via
+    /// [NetworkSyncUpdater::update_status], if any.
+    pub fn last_status(&self) -> Option<(BlockNumber, H256, U256)> {
+        *self.last_status.lock()
+    }
+
+    /// Returns all requests that were sent to this client, in order.
+    pub fn request_log(&self) -> Vec<HeadersRequest> {
+        self.request_log.lock().clone()
+    }
+
+    /// Clears the log of sent requests.
+    pub fn clear_request_log(&self) {
+        self.request_log.lock().clear();
+    }
+
     /// Adds headers to the set.
     pub async fn extend(&self, headers: impl IntoIterator<Item = Header>) {
         let mut lock = self.responses.lock().await;
         lock.extend(headers);
     }
 
+    /// Queues the headers of a response.
+    ///
+    /// Returns the headers back if queueing them would exceed the capacity of the client, so the
+    /// caller can retry once requests drained the queue.
+    pub async fn send_header_response(&self, headers: Vec<Header>) -> Result<(), Vec<Header>> {
+        let mut lock = self.responses.lock().await;
+        if self.capacity.map_or(false, |capacity| lock.len() + headers.len() > capacity) {
+            return Err(headers)
+        }
+        lock.extend(headers);
+        Ok(())
+    }
+
+    /// Queues the headers of a response after applying `mutate` to them, e.g. to tamper with
+    /// their parent hashes.
+    ///
+    /// See also [TestHeadersClient::send_header_response].
+    pub async fn send_corrupted_header_response(
+        &self,
+        mut headers: Vec<Header>,
+        mutate: impl FnOnce(&mut Vec<Header>),
+    ) -> Result<(), Vec<Header>> {
+        mutate(&mut headers);
+        self.send_header_response(headers).await
+    }
+
     /// Clears the set.
     pub async fn clear(&self) {
         let mut lock = self.responses.lock().await;
@@ -218,6 +352,46 @@ impl TestHeadersClient {
         let mut lock = self.error.lock().await;
         lock.replace(err);
     }
+
+    /// Delays all subsequent responses by the given duration.
+    pub async fn set_response_delay(&self, delay: Duration) {
+        let mut lock = self.response_delay.lock().await;
+        lock.replace(delay);
+    }
+
+    /// Fails all subsequent requests whose response is delayed beyond the given timeout, or
+    /// dropped, with [RequestError::Timeout] once the timeout elapsed.
+    ///
+    /// This mirrors the request timeout enforced by the network's fetch client.
+    pub async fn set_request_timeout(&self, timeout: Duration) {
+        let mut lock = self.request_timeout.lock().await;
+        lock.replace(timeout);
+    }
+
+    /// Returns the number of requests that failed with [RequestError::Timeout].
+    pub fn timed_out_requests(&self) -> u64 {
+        self.timed_out_requests.load(Ordering::SeqCst)
+    }
+
+    /// Swallows the response to the next request, which then never resolves, to simulate a
+    /// silent peer.
+    pub fn drop_next_response(&self) {
+        self.drop_next_response.store(true, Ordering::SeqCst);
+    }
+
+    /// Corrupts the given fraction of all subsequent non-empty responses by replacing the parent
+    /// hash of one of their headers.
+    ///
+    /// The corrupted responses are chosen by an rng seeded with `seed`, so runs are reproducible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not in `[0, 1]`.
+    pub async fn set_corruption_rate(&self, rate: f64, seed: u64) {
+        assert!((0.0..=1.0).contains(&rate), "corruption rate must be in [0, 1]");
+        let mut lock = self.corruption.lock().await;
+        lock.replace(ResponseCorruption { rate, rng: StdRng::seed_from_u64(seed) });
+    }
 }
 
 impl DownloadClient for TestHeadersClient {
@@ -240,37 +414,177 @@ impl HeadersClient for TestHeadersClient {
     ) -> Self::Output {
         let responses = self.responses.clone();
         let error = self.error.clone();
+        let corruption = self.corruption.clone();
+        let response_delay = self.response_delay.clone();
+        let request_timeout = self.request_timeout.clone();
+        let timed_out_requests = self.timed_out_requests.clone();
+        let drop_response = self.drop_next_response.swap(false, Ordering::SeqCst);
+        let peer_id = self.peer_id;
 
         self.request_attempts.fetch_add(1, Ordering::SeqCst);
+        self.request_log.lock().push(request.clone());
 
         Box::pin(async move {
+            let delay = *response_delay.lock().await;
+            let timeout = *request_timeout.lock().await;
+            if let Some(timeout) = timeout {
+                if drop_response || delay.map_or(false, |delay| delay > timeout) {
+                    tokio::time::sleep(timeout).await;
+                    timed_out_requests.fetch_add(1, Ordering::SeqCst);
+                    return Err(RequestError::Timeout)
+                }
+            }
+
+            if drop_response {
+                return future::pending().await
+            }
+
+            if let Some(delay) = delay {
+                tokio::time::sleep(delay).await;
+            }
+
             if let Some(err) = &mut *error.lock().await {
                 return Err(err.clone())
             }
 
             let mut lock = responses.lock().await;
             let len = lock.len().min(request.limit as usize);
-            let resp = lock.drain(..len).collect();
-            let with_peer_id = WithPeerId::from((PeerId::default(), resp));
+            let mut resp = lock.drain(..len).collect::<Vec<_>>();
+            if let Some(corruption) = &mut *corruption.lock().await {
+                corruption.maybe_corrupt(&mut resp);
+            }
+            let with_peer_id = WithPeerId::from((peer_id, resp));
             Ok(with_peer_id)
         })
     }
+
+    fn report_good_message(&self, _peer_id: PeerId) {
+        self.good_messages.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// A [HeadersClient] for offline operations, which fails every request with
+/// [RequestError::NetworkDisabled].
+///
+/// This lets code paths that must not touch the network surface a proper error if they
+/// accidentally do.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OfflineHeadersClient;
+
+impl DownloadClient for OfflineHeadersClient {
+    fn report_bad_message(&self, _peer_id: PeerId) {
+        // noop
+    }
+
+    fn num_connected_peers(&self) -> usize {
+        0
+    }
+}
+
+impl HeadersClient for OfflineHeadersClient {
+    type Output = future::Ready<PeerRequestResult<Vec<Header>>>;
+
+    fn get_headers_with_priority(
+        &self,
+        _request: HeadersRequest,
+        _priority: Priority,
+    ) -> Self::Output {
+        future::ready(Err(RequestError::NetworkDisabled))
+    }
+}
+
+impl NetworkSyncUpdater for TestHeadersClient {
+    fn update_sync_state(&self, _state: SyncState) {
+        // noop
+    }
+
+    fn update_status(&self, head: Head) {
+        *self.last_status.lock() = Some((head.number, head.hash, head.total_difficulty));
+    }
 }
 
 /// Consensus engine implementation for testing
 #[derive(Debug)]
 pub struct TestConsensus {
+    /// Watcher over the forkchoice state
+    channel: (Arc<watch::Sender<ForkchoiceState>>, watch::Receiver<ForkchoiceState>),
     /// Flag whether the header validation should purposefully fail
     fail_validation: AtomicBool,
+    /// Queued results of [Consensus::validate_header], consumed one per call.
+    validation_results: parking_lot::Mutex<VecDeque<Result<(), ConsensusError>>>,
+    /// Queued results of [Consensus::validate_block], consumed one per call.
+    block_validation_results: parking_lot::Mutex<VecDeque<Result<(), ConsensusError>>>,
+    /// The `(hash, parent_hash)` pairs of all headers passed to [Consensus::validate_header].
+    validation_log: parking_lot::Mutex<Vec<(H256, H256)>>,
 }
 
 impl Default for TestConsensus {
     fn default() -> Self {
-        Self { fail_validation: AtomicBool::new(false) }
+        let (tx, rx) = watch::channel(ForkchoiceState::default());
+        Self {
+            channel: (Arc::new(tx), rx),
+            fail_validation: AtomicBool::new(false),
+            validation_results: Default::default(),
+            block_validation_results: Default::default(),
+            validation_log: Default::default(),
+        }
     }
 }
 
 impl TestConsensus {
+    /// Creates a consensus whose forkchoice state is updated with each of the given states in
+    /// order, after waiting for its delay.
+    ///
+    /// Every delay is relative to the previous update. The updates are sent by a spawned task,
+    /// which stops once the consensus is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn with_forkchoice_script(states: Vec<(Duration, ForkchoiceState)>) -> Self {
+        let consensus = Self::default();
+        let sender = consensus.channel.0.clone();
+        tokio::spawn(async move {
+            for (delay, state) in states {
+                tokio::time::sleep(delay).await;
+                if sender.send(state).is_err() {
+                    break
+                }
+            }
+        });
+        consensus
+    }
+
+    /// Returns a receiver over the forkchoice state.
+    pub fn fork_choice_state(&self) -> watch::Receiver<ForkchoiceState> {
+        self.channel.1.clone()
+    }
+
+    /// Updates the head block hash, the safe and finalized block hashes are left unchanged.
+    pub fn update_tip(&self, tip: H256) {
+        let ForkchoiceState { safe_block_hash, finalized_block_hash, .. } =
+            *self.channel.1.borrow();
+        self.update_forkchoice(tip, safe_block_hash, finalized_block_hash)
+    }
+
+    /// Updates the forkchoice state.
+    pub fn update_forkchoice(&self, head: H256, safe: H256, finalized: H256) {
+        let state = ForkchoiceState {
+            head_block_hash: head,
+            safe_block_hash: safe,
+            finalized_block_hash: finalized,
+        };
+        self.notify_fork_choice_state(state).expect("channel closed")
+    }
+
+    /// Sends the given forkchoice state to all subscribers.
+    pub fn notify_fork_choice_state(
+        &self,
+        state: ForkchoiceState,
+    ) -> Result<(), SendError<ForkchoiceState>> {
+        self.channel.0.send(state)
+    }
+
     /// Get the failed validation flag.
     pub fn fail_validation(&self) -> bool {
         self.fail_validation.load(Ordering::SeqCst)
@@ -280,28 +594,57 @@ impl TestConsensus {
     pub fn set_fail_validation(&self, val: bool) {
         self.fail_validation.store(val, Ordering::SeqCst)
     }
-}
 
-#[async_trait::async_trait]
-impl Consensus for TestConsensus {
-    fn validate_header(&self, _header: &SealedHeader) -> Result<(), ConsensusError> {
+    /// Replaces the queued results of [Consensus::validate_header].
+    ///
+    /// Each call consumes the next result. Once the queue is empty, the result is determined by
+    /// the [fail_validation](Self::fail_validation) flag.
+    pub fn set_validation_results(&self, results: Vec<Result<(), ConsensusError>>) {
+        *self.validation_results.lock() = results.into();
+    }
+
+    /// Replaces the queued results of [Consensus::validate_block].
+    ///
+    /// Each call consumes the next result, independent of the header validation results. Once the
+    /// queue is empty, the result is determined by the [fail_validation](Self::fail_validation)
+    /// flag.
+    pub fn set_block_validation_results(&self, results: Vec<Result<(), ConsensusError>>) {
+        *self.block_validation_results.lock() = results.into();
+    }
+
+    /// Returns the `(hash, parent_hash)` pairs of all headers passed to
+    /// [Consensus::validate_header], in call order.
+    pub fn validation_log(&self) -> Vec<(H256, H256)> {
+        self.validation_log.lock().clone()
+    }
+
+    /// Returns the validation result according to the [fail_validation](Self::fail_validation)
+    /// flag.
+    fn fallback_result(&self) -> Result<(), ConsensusError> {
         if self.fail_validation() {
             Err(consensus::ConsensusError::BaseFeeMissing)
         } else {
             Ok(())
         }
     }
+}
+
+#[async_trait::async_trait]
+impl Consensus for TestConsensus {
+    fn validate_header(&self, header: &SealedHeader) -> Result<(), ConsensusError> {
+        self.validation_log.lock().push((header.hash(), header.parent_hash));
+        if let Some(result) = self.validation_results.lock().pop_front() {
+            return result
+        }
+        self.fallback_result()
+    }
 
     fn validate_header_against_parent(
         &self,
         header: &SealedHeader,
         parent: &SealedHeader,
     ) -> Result<(), ConsensusError> {
-        if self.fail_validation() {
-            Err(consensus::ConsensusError::BaseFeeMissing)
-        } else {
-            Ok(())
-        }
+        self.fallback_result()
     }
 
     fn validate_header_with_total_difficulty(
@@ -309,18 +652,197 @@ impl Consensus for TestConsensus {
         header: &Header,
         total_difficulty: U256,
     ) -> Result<(), ConsensusError> {
-        if self.fail_validation() {
-            Err(consensus::ConsensusError::BaseFeeMissing)
-        } else {
-            Ok(())
-        }
+        self.fallback_result()
     }
 
     fn validate_block(&self, _block: &SealedBlock) -> Result<(), consensus::ConsensusError> {
-        if self.fail_validation() {
-            Err(consensus::ConsensusError::BaseFeeMissing)
-        } else {
-            Ok(())
+        if let Some(result) = self.block_validation_results.lock().pop_front() {
+            return result
+        }
+        self.fallback_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::generators::random_header_range;
+    use reth_primitives::BlockHashOrNumber;
+
+    async fn corrupted_responses(rate: f64, seed: u64) -> Vec<bool> {
+        let client = TestHeadersClient::default();
+        let headers = (0..10).map(|number| Header { number, ..Default::default() });
+        client.extend(headers.clone()).await;
+        client.set_corruption_rate(rate, seed).await;
+
+        let request = HeadersRequest {
+            start: BlockHashOrNumber::Number(0),
+            limit: 1,
+            direction: HeadersDirection::Rising,
+        };
+        let mut corrupted = Vec::new();
+        for expected in headers {
+            let response = client.get_headers(request.clone()).await.unwrap().into_data();
+            corrupted.push(response != vec![expected]);
+        }
+        corrupted
+    }
+
+    #[tokio::test]
+    async fn downloader_omits_dropped_headers() {
+        let headers = random_header_range(0..10, H256::zero());
+        let mut downloader = TestHeaderDownloader::with_gaps(headers.clone(), vec![3, 7]);
+
+        let downloaded = downloader.next().await.unwrap().unwrap();
+        assert!(downloader.next().await.is_none());
+
+        let numbers = downloaded.iter().map(|header| header.number).collect::<Vec<_>>();
+        assert_eq!(numbers, vec![0, 1, 2, 4, 5, 6, 8, 9]);
+        // headers around the gaps still link to the omitted headers
+        assert_eq!(downloaded[3].parent_hash, headers[3].hash());
+        assert_eq!(downloaded[6].parent_hash, headers[7].hash());
+    }
+
+    #[test]
+    fn update_status_is_observable() {
+        let client = TestHeadersClient::default();
+        assert_eq!(client.last_status(), None);
+
+        let hash = H256::random();
+        client.update_status(Head {
+            number: 10,
+            hash,
+            total_difficulty: U256::from(100),
+            ..Default::default()
+        });
+        client.update_status(Head {
+            number: 11,
+            hash,
+            total_difficulty: U256::from(110),
+            ..Default::default()
+        });
+        assert_eq!(client.last_status(), Some((11, hash, U256::from(110))));
+    }
+
+    #[tokio::test]
+    async fn scripted_forkchoice_states() {
+        let state = |head| ForkchoiceState { head_block_hash: head, ..Default::default() };
+        let (first, second) = (H256::random(), H256::random());
+        let consensus = TestConsensus::with_forkchoice_script(vec![
+            (Duration::from_millis(10), state(first)),
+            (Duration::from_millis(200), state(second)),
+        ]);
+        let mut rx = consensus.fork_choice_state();
+
+        rx.changed().await.unwrap();
+        assert_eq!(rx.borrow().head_block_hash, first);
+        rx.changed().await.unwrap();
+        assert_eq!(rx.borrow().head_block_hash, second);
+    }
+
+    #[tokio::test]
+    async fn update_tip_preserves_safe_and_finalized() {
+        let consensus = TestConsensus::default();
+        let mut rx = consensus.fork_choice_state();
+        let (head, safe, finalized) = (H256::random(), H256::random(), H256::random());
+
+        consensus.update_forkchoice(head, safe, finalized);
+        rx.changed().await.unwrap();
+        assert_eq!(
+            *rx.borrow(),
+            ForkchoiceState {
+                head_block_hash: head,
+                safe_block_hash: safe,
+                finalized_block_hash: finalized
+            }
+        );
+
+        let tip = H256::random();
+        consensus.update_tip(tip);
+        rx.changed().await.unwrap();
+        assert_eq!(
+            *rx.borrow(),
+            ForkchoiceState {
+                head_block_hash: tip,
+                safe_block_hash: safe,
+                finalized_block_hash: finalized
+            }
+        );
+    }
+
+    #[test]
+    fn consumes_queued_validation_results() {
+        let consensus = TestConsensus::default();
+        let err = ConsensusError::TimestampIsInPast { parent_timestamp: 2, timestamp: 1 };
+        consensus.set_validation_results(vec![Ok(()), Err(err.clone()), Ok(())]);
+
+        let header = SealedHeader::default();
+        assert_eq!(consensus.validate_header(&header), Ok(()));
+        assert_eq!(consensus.validate_header(&header), Err(err));
+        assert_eq!(consensus.validate_header(&header), Ok(()));
+
+        // falls back to the flag once the queue is empty
+        assert_eq!(consensus.validate_header(&header), Ok(()));
+        consensus.set_fail_validation(true);
+        assert_eq!(consensus.validate_header(&header), Err(ConsensusError::BaseFeeMissing));
+    }
+
+    #[tokio::test]
+    async fn bounded_response_queue() {
+        let client = TestHeadersClient::with_capacity(4);
+        for number in 0..3 {
+            client
+                .send_header_response(vec![Header { number, ..Default::default() }])
+                .await
+                .unwrap();
         }
+
+        let response = vec![3, 4].into_iter().map(|number| Header { number, ..Default::default() });
+        let rejected = client.send_header_response(response.collect()).await.unwrap_err();
+        assert_eq!(rejected.len(), 2);
+
+        let request = HeadersRequest {
+            start: BlockHashOrNumber::Number(0),
+            limit: 2,
+            direction: HeadersDirection::Rising,
+        };
+        let drained = client.get_headers(request.clone()).await.unwrap().into_data();
+        assert_eq!(drained.iter().map(|h| h.number).collect::<Vec<_>>(), vec![0, 1]);
+
+        client.send_header_response(rejected).await.unwrap();
+        let drained = client.get_headers(request).await.unwrap().into_data();
+        assert_eq!(drained.iter().map(|h| h.number).collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn delayed_and_dropped_responses_time_out() {
+        let client = TestHeadersClient::default();
+        client.extend((0..2).map(|number| Header { number, ..Default::default() })).await;
+        let request = HeadersRequest {
+            start: BlockHashOrNumber::Number(0),
+            limit: 1,
+            direction: HeadersDirection::Rising,
+        };
+        let timeout = Duration::from_millis(20);
+
+        client.set_response_delay(timeout * 5).await;
+        let res = tokio::time::timeout(timeout, client.get_headers(request.clone())).await;
+        assert!(res.is_err());
+
+        client.set_response_delay(Duration::ZERO).await;
+        client.drop_next_response();
+        let res = tokio::time::timeout(timeout, client.get_headers(request.clone())).await;
+        assert!(res.is_err());
+
+        // only the next response is dropped
+        let res = tokio::time::timeout(timeout, client.get_headers(request)).await;
+        assert_eq!(res.unwrap().unwrap().into_data().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn corruption_rate_is_deterministic() {
+        assert!(corrupted_responses(0.0, 1).await.iter().all(|corrupted| !corrupted));
+        assert!(corrupted_responses(1.0, 1).await.iter().all(|corrupted| *corrupted));
+        assert_eq!(corrupted_responses(0.5, 42).await, corrupted_responses(0.5, 42).await);
     }
 }
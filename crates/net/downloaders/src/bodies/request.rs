@@ -238,8 +238,12 @@ mod tests {
         test_utils::{generate_bodies, TestBodiesClient, TEST_SCOPE},
     };
     use reth_interfaces::{
+        consensus::ConsensusError,
         p2p::bodies::response::BlockResponse,
-        test_utils::{generators::random_header_range, TestConsensus},
+        test_utils::{
+            generators::{random_block_range, random_header_range},
+            TestConsensus,
+        },
     };
     use reth_primitives::H256;
     use std::sync::Arc;
@@ -288,4 +292,43 @@ mod tests {
             (headers.into_iter().filter(|h| !h.is_empty()).count() as u64 + 1) / 2
         );
     }
+
+    /// Check that a body failing block validation is rejected, even though its header is valid.
+    #[tokio::test]
+    async fn request_rejects_invalid_block_body() {
+        let blocks = random_block_range(0..=2, H256::zero(), 1..2);
+        let headers = blocks.iter().map(|block| block.header.clone()).collect::<Vec<_>>();
+
+        let consensus = Arc::new(TestConsensus::default());
+        consensus.set_block_validation_results(vec![Ok(()), Err(ConsensusError::BaseFeeMissing)]);
+        for header in &headers {
+            assert_eq!(consensus.validate_header(header), Ok(()));
+        }
+
+        let mut fut = BodiesRequestFuture::new(
+            Arc::new(TestBodiesClient::default()),
+            consensus,
+            DownloaderMetrics::new(TEST_SCOPE),
+        )
+        .with_headers(headers);
+
+        let bodies = blocks
+            .iter()
+            .map(|block| BlockBody {
+                transactions: block.body.clone(),
+                ommers: block.ommers.clone(),
+                withdrawals: block.withdrawals.clone(),
+            })
+            .collect();
+        assert_eq!(
+            fut.try_buffer_blocks(bodies),
+            Err(DownloadError::BodyValidation {
+                hash: blocks[1].hash(),
+                error: ConsensusError::BaseFeeMissing
+            })
+        );
+        // the valid block is buffered and the invalid one is put back
+        assert_eq!(fut.buffer, vec![BlockResponse::Full(blocks[0].clone())]);
+        assert_eq!(fut.pending_headers.front(), Some(&blocks[1].header));
+    }
 }
@@ -356,6 +356,8 @@ where
                 // update total downloaded metric
                 self.metrics.total_downloaded.increment(headers.len() as u64);
 
+                self.ensure_max_response_len(&request, peer_id, headers.len())?;
+
                 // sort headers from highest to lowest block number
                 headers.sort_unstable_by_key(|h| Reverse(h.number));
 
@@ -433,6 +435,27 @@ where
         }
     }
 
+    /// Returns an error if a response with `len` headers exceeds
+    /// [HeaderDownloader::max_response_len].
+    #[allow(clippy::result_large_err)]
+    fn ensure_max_response_len(
+        &self,
+        request: &HeadersRequest,
+        peer_id: PeerId,
+        len: usize,
+    ) -> Result<(), ReverseHeadersDownloaderError> {
+        let max = self.max_response_len();
+        if len > max {
+            return Err(HeadersResponseError {
+                request: request.clone(),
+                peer_id: Some(peer_id),
+                error: DownloadError::TooManyHeaders { received: len, expected: max },
+            }
+            .into())
+        }
+        Ok(())
+    }
+
     /// Invoked when we received a response
     #[allow(clippy::result_large_err)]
     fn on_headers_outcome(
@@ -451,6 +474,8 @@ where
 
                 trace!(target: "downloaders::headers", len=%headers.len(), "Received headers response");
 
+                self.ensure_max_response_len(&request, peer_id, headers.len())?;
+
                 if headers.is_empty() {
                     return Err(HeadersResponseError {
                         request,
@@ -1195,6 +1220,7 @@ mod tests {
     use assert_matches::assert_matches;
     use reth_interfaces::test_utils::{TestConsensus, TestHeadersClient};
     use reth_primitives::SealedHeader;
+    use std::time::Duration;
 
     /// Tests that `replace_number` works the same way as Option::replace
     #[test]
@@ -1402,6 +1428,59 @@ mod tests {
         assert!(downloader.next().await.is_none());
     }
 
+    /// Tests that requests which are answered after the request timeout are retried
+    #[tokio::test]
+    async fn retries_timed_out_requests() {
+        reth_tracing::init_test_tracing();
+
+        let client = Arc::new(TestHeadersClient::default());
+        let timeout = Duration::from_millis(50);
+        client.set_request_timeout(timeout).await;
+        client.set_response_delay(timeout * 4).await;
+
+        let p3 = SealedHeader::default();
+        let p2 = child_header(&p3);
+        let p1 = child_header(&p2);
+        let p0 = child_header(&p1);
+
+        let mut downloader = ReverseHeadersDownloaderBuilder::default()
+            .stream_batch_size(3)
+            .request_limit(3)
+            .build(Arc::clone(&client), Arc::new(TestConsensus::default()));
+        downloader.update_local_head(p3.clone());
+        downloader.update_sync_target(SyncTarget::Tip(p0.hash()));
+
+        client
+            .extend(vec![
+                p0.as_ref().clone(),
+                p1.as_ref().clone(),
+                p2.as_ref().clone(),
+                p3.as_ref().clone(),
+            ])
+            .await;
+
+        // the peer only starts answering in time after the first request timed out
+        let (headers, _) = tokio::join!(downloader.next(), async {
+            tokio::time::sleep(timeout + timeout / 2).await;
+            client.set_response_delay(Duration::ZERO).await;
+        });
+        assert_eq!(headers.unwrap(), Ok(vec![p0, p1, p2]));
+        assert!(client.timed_out_requests() > 0);
+
+        // the timed out sync target request was sent again
+        let log = client.request_log();
+        let first = &log[0];
+        let resent = log
+            .iter()
+            .filter(|req| {
+                req.start == first.start &&
+                    req.limit == first.limit &&
+                    req.direction == first.direction
+            })
+            .count();
+        assert!(resent > 1);
+    }
+
     #[tokio::test]
     async fn download_one_by_one() {
         reth_tracing::init_test_tracing();
@@ -1436,4 +1515,133 @@ mod tests {
         assert_eq!(headers, Ok(vec![p2]));
         assert!(downloader.next().await.is_none());
     }
+
+    #[tokio::test]
+    async fn rejects_tampered_sync_target() {
+        reth_tracing::init_test_tracing();
+        let p1 = SealedHeader::default();
+        let p0 = child_header(&p1);
+
+        let client = Arc::new(TestHeadersClient::default());
+        let mut downloader = ReverseHeadersDownloaderBuilder::default()
+            .build(Arc::clone(&client), Arc::new(TestConsensus::default()));
+        downloader.update_local_head(p1);
+        downloader.update_sync_target(SyncTarget::Tip(p0.hash()));
+
+        client
+            .send_corrupted_header_response(vec![p0.as_ref().clone()], |headers| {
+                headers[0].parent_hash = H256::random();
+            })
+            .await
+            .unwrap();
+
+        let outcome = downloader.sync_target_request.take().unwrap().await;
+        assert_matches!(
+            downloader.on_sync_target_outcome(outcome),
+            Err(ReverseHeadersDownloaderError::Response(HeadersResponseError {
+                error: DownloadError::InvalidTip { expected, .. },
+                ..
+            })) if expected == p0.hash()
+        );
+    }
+
+    #[tokio::test]
+    async fn download_requests_batches() {
+        reth_tracing::init_test_tracing();
+        let local_head = SealedHeader::default();
+        let mut headers = vec![local_head.clone()];
+        for _ in 0..10 {
+            headers.push(child_header(headers.last().unwrap()));
+        }
+        headers.reverse();
+        let tip = headers[0].clone();
+
+        let client = Arc::new(TestHeadersClient::default());
+        let mut downloader = ReverseHeadersDownloaderBuilder::default()
+            .stream_batch_size(10)
+            .request_limit(4)
+            .min_concurrent_requests(1)
+            .max_concurrent_requests(1)
+            .build(Arc::clone(&client), Arc::new(TestConsensus::default()));
+        downloader.update_local_head(local_head);
+        downloader.update_sync_target(SyncTarget::Tip(tip.hash()));
+
+        client.extend(headers.iter().map(|header| header.as_ref().clone())).await;
+
+        let downloaded = downloader.next().await.unwrap();
+        assert_eq!(downloaded, Ok(headers[..10].to_vec()));
+
+        // the sync target request, followed by batches towards the local head
+        let limits = client.request_log().into_iter().map(|req| req.limit).collect::<Vec<_>>();
+        assert_eq!(limits, vec![1, 4, 4, 1]);
+
+        client.clear_request_log();
+        assert!(client.request_log().is_empty());
+    }
+
+    #[tokio::test]
+    async fn validation_log_links_parents() {
+        reth_tracing::init_test_tracing();
+        let local_head = SealedHeader::default();
+        let mut headers = vec![local_head.clone()];
+        for _ in 0..10 {
+            headers.push(child_header(headers.last().unwrap()));
+        }
+        headers.reverse();
+        let tip = headers[0].clone();
+
+        let client = Arc::new(TestHeadersClient::default());
+        let consensus = Arc::new(TestConsensus::default());
+        let mut downloader = ReverseHeadersDownloaderBuilder::default()
+            .stream_batch_size(10)
+            .request_limit(4)
+            .build(Arc::clone(&client), Arc::clone(&consensus));
+        downloader.update_local_head(local_head.clone());
+        downloader.update_sync_target(SyncTarget::Tip(tip.hash()));
+
+        client.extend(headers.iter().map(|header| header.as_ref().clone())).await;
+
+        let downloaded = downloader.next().await.unwrap();
+        assert_eq!(downloaded, Ok(headers[..10].to_vec()));
+
+        // every downloaded header is validated once, from the tip down to the local head
+        let log = consensus.validation_log();
+        let hashes = log.iter().map(|(hash, _)| *hash).collect::<Vec<_>>();
+        assert_eq!(hashes, headers[..10].iter().map(|header| header.hash()).collect::<Vec<_>>());
+        for pair in log.windows(2) {
+            assert_eq!(pair[0].1, pair[1].0);
+        }
+        assert_eq!(log.last().unwrap().1, local_head.hash());
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_response() {
+        reth_tracing::init_test_tracing();
+        let p1 = SealedHeader::default();
+        let p0 = child_header(&p1);
+
+        let client = Arc::new(TestHeadersClient::default());
+        let mut downloader = ReverseHeadersDownloaderBuilder::default()
+            .build(Arc::clone(&client), Arc::new(TestConsensus::default()));
+        downloader.update_local_head(p1);
+        downloader.update_sync_target(SyncTarget::Tip(p0.hash()));
+
+        let max = downloader.max_response_len();
+        let request = HeadersRequest {
+            start: p0.hash().into(),
+            limit: 1,
+            direction: HeadersDirection::Falling,
+        };
+        let headers = vec![p0.as_ref().clone(); max + 1];
+        let outcome =
+            HeadersRequestOutcome { request, outcome: Ok((PeerId::default(), headers).into()) };
+        assert_matches!(
+            downloader.on_sync_target_outcome(outcome),
+            Err(ReverseHeadersDownloaderError::Response(HeadersResponseError {
+                error: DownloadError::TooManyHeaders { received, expected },
+                peer_id: Some(_),
+                ..
+            })) if received == max + 1 && expected == max
+        );
+    }
 }
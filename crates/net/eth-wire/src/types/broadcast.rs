@@ -7,6 +7,7 @@ use reth_rlp::{
     Decodable, Encodable, RlpDecodable, RlpDecodableWrapper, RlpEncodable, RlpEncodableWrapper,
 };
 use std::sync::Arc;
+use thiserror::Error;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -80,6 +81,38 @@ pub struct Transactions(
     pub Vec<TransactionSigned>,
 );
 
+// === impl Transactions ===
+
+impl Transactions {
+    /// Returns an iterator over the transactions.
+    pub fn iter(&self) -> std::slice::Iter<'_, TransactionSigned> {
+        self.0.iter()
+    }
+
+    /// Returns the number of transactions.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no transactions.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the combined RLP encoded length of all transactions, excluding the list header.
+    ///
+    /// This is used to budget how many transactions fit into a single broadcast.
+    pub fn total_size(&self) -> usize {
+        self.0.iter().map(Encodable::length).sum()
+    }
+
+    /// Consumes the type and returns the hashes of all transactions, e.g. for announcing them
+    /// instead of broadcasting the full bodies.
+    pub fn into_hashes(self) -> Vec<H256> {
+        self.0.into_iter().map(|tx| tx.hash()).collect()
+    }
+}
+
 impl From<Vec<TransactionSigned>> for Transactions {
     fn from(txs: Vec<TransactionSigned>) -> Self {
         Transactions(txs)
@@ -129,6 +162,48 @@ impl NewPooledTransactionHashes {
         }
     }
 
+    /// Decodes the announcement in the format of the given negotiated `eth` protocol version.
+    ///
+    /// eth/68 and later announce transaction types and sizes alongside the hashes, earlier
+    /// versions announce a plain list of hashes.
+    pub fn decode_for_version(
+        version: EthVersion,
+        buf: &mut &[u8],
+    ) -> Result<Self, reth_rlp::DecodeError> {
+        if version >= EthVersion::Eth68 {
+            Ok(Self::Eth68(NewPooledTransactionHashes68::decode(buf)?))
+        } else {
+            Ok(Self::Eth66(NewPooledTransactionHashes66::decode(buf)?))
+        }
+    }
+
+    /// Encodes the announcement for a peer on the given negotiated `eth` protocol version.
+    ///
+    /// Returns an error without writing anything if the format of the announcement is not valid on
+    /// that version, see [`NewPooledTransactionHashes::is_valid_for_version`].
+    pub fn encode_for_version(
+        &self,
+        version: EthVersion,
+        out: &mut dyn bytes::BufMut,
+    ) -> Result<(), AnnouncementVersionMismatch> {
+        if !self.is_valid_for_version(version) {
+            return Err(AnnouncementVersionMismatch { version })
+        }
+        match self {
+            NewPooledTransactionHashes::Eth66(msg) => msg.encode(out),
+            NewPooledTransactionHashes::Eth68(msg) => msg.encode(out),
+        }
+        Ok(())
+    }
+
+    /// Returns all transaction hashes.
+    pub fn hashes(&self) -> &[H256] {
+        match self {
+            NewPooledTransactionHashes::Eth66(msg) => &msg.0,
+            NewPooledTransactionHashes::Eth68(msg) => &msg.hashes,
+        }
+    }
+
     /// Returns an iterator over all transaction hashes.
     pub fn iter_hashes(&self) -> impl Iterator<Item = &H256> + '_ {
         match self {
@@ -183,6 +258,15 @@ impl NewPooledTransactionHashes {
     }
 }
 
+/// Error returned by [`NewPooledTransactionHashes::encode_for_version`] if the format of the
+/// announcement is not valid on the negotiated version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("announcement format is not valid on {version:?}")]
+pub struct AnnouncementVersionMismatch {
+    /// The negotiated `eth` protocol version.
+    pub version: EthVersion,
+}
+
 impl From<NewPooledTransactionHashes> for EthMessage {
     fn from(value: NewPooledTransactionHashes) -> Self {
         match value {
@@ -258,6 +342,43 @@ pub struct NewPooledTransactionHashes68 {
     pub hashes: Vec<H256>,
 }
 
+// === impl NewPooledTransactionHashes68 ===
+
+impl NewPooledTransactionHashes68 {
+    /// The `eth` protocol versions on which this message is valid.
+    ///
+    /// Earlier versions announce hashes only, see [`NewPooledTransactionHashes66`].
+    pub const SUPPORTED_VERSIONS: &'static [EthVersion] = &[EthVersion::Eth68];
+
+    /// Checks that the `types`, `sizes` and `hashes` vectors have the same length.
+    ///
+    /// Announcements that fail this check are malformed and should be rejected before any of the
+    /// announced hashes are processed.
+    pub fn validate(&self) -> Result<(), AnnouncementLengthMismatch> {
+        if self.types.len() != self.hashes.len() || self.sizes.len() != self.hashes.len() {
+            return Err(AnnouncementLengthMismatch {
+                types: self.types.len(),
+                sizes: self.sizes.len(),
+                hashes: self.hashes.len(),
+            })
+        }
+        Ok(())
+    }
+}
+
+/// Error returned by [`NewPooledTransactionHashes68::validate`] if the announced types, sizes and
+/// hashes do not line up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("mismatched eth/68 announcement: {types} types, {sizes} sizes, {hashes} hashes")]
+pub struct AnnouncementLengthMismatch {
+    /// The number of announced transaction types.
+    pub types: usize,
+    /// The number of announced transaction sizes.
+    pub sizes: usize,
+    /// The number of announced transaction hashes.
+    pub hashes: usize,
+}
+
 impl Encodable for NewPooledTransactionHashes68 {
     fn encode(&self, out: &mut dyn bytes::BufMut) {
         #[derive(RlpEncodable)]
@@ -317,6 +438,47 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn eth68_announcement_versions() {
+        let versions = NewPooledTransactionHashes68::SUPPORTED_VERSIONS;
+        assert!(!versions.contains(&EthVersion::Eth66));
+        assert!(!versions.contains(&EthVersion::Eth67));
+        assert!(versions.contains(&EthVersion::Eth68));
+    }
+
+    #[test]
+    fn roundtrip_announcement_for_version() {
+        let hashes = vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+        let eth66 = NewPooledTransactionHashes::Eth66(hashes.clone().into());
+        let eth68 = NewPooledTransactionHashes::Eth68(NewPooledTransactionHashes68 {
+            types: vec![0x00, 0x02],
+            sizes: vec![100, 200],
+            hashes: hashes.clone(),
+        });
+
+        for (msg, version) in
+            [(&eth66, EthVersion::Eth66), (&eth66, EthVersion::Eth67), (&eth68, EthVersion::Eth68)]
+        {
+            let mut encoded = BytesMut::new();
+            msg.encode_for_version(version, &mut encoded).unwrap();
+            let decoded =
+                NewPooledTransactionHashes::decode_for_version(version, &mut &encoded[..]).unwrap();
+            assert_eq!(&decoded, msg);
+            assert_eq!(decoded.hashes(), &hashes[..]);
+        }
+
+        let mut encoded = BytesMut::new();
+        assert_eq!(
+            eth66.encode_for_version(EthVersion::Eth68, &mut encoded),
+            Err(AnnouncementVersionMismatch { version: EthVersion::Eth68 })
+        );
+        assert_eq!(
+            eth68.encode_for_version(EthVersion::Eth66, &mut encoded),
+            Err(AnnouncementVersionMismatch { version: EthVersion::Eth66 })
+        );
+        assert!(encoded.is_empty());
+    }
+
     /// Takes as input a struct / encoded hex message pair, ensuring that we encode to the exact hex
     /// message, and decode to the exact struct.
     fn test_encoding_vector<T: Encodable + Decodable + PartialEq + std::fmt::Debug>(
@@ -464,4 +626,56 @@ mod tests {
             test_encoding_vector(vector);
         }
     }
+
+    #[test]
+    fn eth_68_validate_lengths() {
+        let hash = H256::from_low_u64_be(1);
+        let valid = NewPooledTransactionHashes68 {
+            types: vec![0x02, 0x00],
+            sizes: vec![0xb6, 0x64],
+            hashes: vec![hash, hash],
+        };
+        assert_eq!(valid.validate(), Ok(()));
+        assert_eq!(NewPooledTransactionHashes68::default().validate(), Ok(()));
+
+        // decodes fine, but announces two types and sizes for a single hash
+        let encoded = hex!(
+            "e9820200c481b664e1a0fecbed04c7b88d8e7221a0a3f5dc33f220212347fc167459ea5cc9c3eb4c1124"
+        );
+        let decoded = NewPooledTransactionHashes68::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(
+            decoded.validate(),
+            Err(AnnouncementLengthMismatch { types: 2, sizes: 2, hashes: 1 })
+        );
+
+        let mut reencoded = BytesMut::new();
+        decoded.encode(&mut reencoded);
+        assert_eq!(&reencoded[..], &encoded[..]);
+    }
+
+    #[test]
+    fn transactions_roundtrip() {
+        use reth_primitives::{Signature, Transaction, TxEip1559, TxLegacy};
+
+        let txs = Transactions(vec![
+            TransactionSigned::from_transaction_and_signature(
+                Transaction::Legacy(TxLegacy { nonce: 1, ..Default::default() }),
+                Signature::default(),
+            ),
+            TransactionSigned::from_transaction_and_signature(
+                Transaction::Eip1559(TxEip1559 { chain_id: 1, nonce: 2, ..Default::default() }),
+                Signature::default(),
+            ),
+        ]);
+        assert_eq!(txs.len(), 2);
+        assert_eq!(txs.total_size(), txs.0[0].length() + txs.0[1].length());
+
+        let mut encoded = BytesMut::new();
+        txs.encode(&mut encoded);
+        // a plain list of transactions, without a request id
+        assert_eq!(encoded.len(), reth_rlp::length_of_length(txs.total_size()) + txs.total_size());
+
+        let decoded = Transactions::decode(&mut encoded.as_ref()).unwrap();
+        assert_eq!(decoded, txs);
+    }
 }
@@ -1,8 +1,9 @@
 #![allow(missing_docs)]
 use super::{
     broadcast::NewBlockHashes, BlockBodies, BlockHeaders, GetBlockBodies, GetBlockHeaders,
-    GetNodeData, GetPooledTransactions, GetReceipts, NewBlock, NewPooledTransactionHashes66,
-    NewPooledTransactionHashes68, NodeData, PooledTransactions, Receipts, Status, Transactions,
+    GetNodeData, GetPooledTransactions, GetReceipts, NewBlock, NewPooledTransactionHashes,
+    NewPooledTransactionHashes66, NewPooledTransactionHashes68, NodeData, PooledTransactions,
+    Receipts, Status, Transactions,
 };
 use crate::{errors::EthStreamError, EthVersion, SharedTransactions};
 use reth_primitives::bytes::{Buf, BufMut};
@@ -33,15 +34,7 @@ impl ProtocolMessage {
             EthMessageID::NewBlock => EthMessage::NewBlock(Box::new(NewBlock::decode(buf)?)),
             EthMessageID::Transactions => EthMessage::Transactions(Transactions::decode(buf)?),
             EthMessageID::NewPooledTransactionHashes => {
-                if version >= EthVersion::Eth68 {
-                    EthMessage::NewPooledTransactionHashes68(NewPooledTransactionHashes68::decode(
-                        buf,
-                    )?)
-                } else {
-                    EthMessage::NewPooledTransactionHashes66(NewPooledTransactionHashes66::decode(
-                        buf,
-                    )?)
-                }
+                NewPooledTransactionHashes::decode_for_version(version, buf)?.into()
             }
             EthMessageID::GetBlockHeaders => {
                 let request_pair = RequestPair::<GetBlockHeaders>::decode(buf)?;
@@ -1,11 +1,34 @@
 //! Implements the `GetPooledTransactions` and `PooledTransactions` message types.
+use crate::{EthVersion, Transactions};
+use bytes::BytesMut;
 use reth_codecs::derive_arbitrary;
-use reth_primitives::{TransactionSigned, H256};
-use reth_rlp::{RlpDecodableWrapper, RlpEncodableWrapper};
+use reth_primitives::{
+    Address, Signature, TransactionSigned, TransactionSignedEcRecovered, TxType, H256, SECP256K1N,
+    SECP256K1N_HALF,
+};
+use reth_rlp::{
+    length_of_length, Decodable, DecodeError, Encodable, Header, RlpDecodableWrapper,
+    RlpEncodableWrapper,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+};
+use thiserror::Error;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// The RLP encoded length of a single hash, a 32 byte string and its single byte header.
+const ENCODED_HASH_LEN: usize = 1 + 32;
+
+/// The minimum number of transactions for which [`PooledTransactions::recover_signers_par`]
+/// recovers the signers in parallel.
+///
+/// Recovering a single signer takes tens of microseconds, so smaller batches are not worth the
+/// overhead of distributing them across threads.
+pub const PARALLEL_SENDER_RECOVERY_THRESHOLD: usize = 32;
+
 /// A list of transaction hashes that the peer would like transaction bodies for.
 #[derive_arbitrary(rlp)]
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper, Default)]
@@ -15,6 +38,104 @@ pub struct GetPooledTransactions(
     pub Vec<H256>,
 );
 
+// === impl GetPooledTransactions ===
+
+impl GetPooledTransactions {
+    /// The `eth` protocol versions on which this message is valid.
+    pub const SUPPORTED_VERSIONS: &'static [EthVersion] =
+        &[EthVersion::Eth66, EthVersion::Eth67, EthVersion::Eth68];
+
+    /// Returns the number of requested hashes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if no hashes are requested.
+    ///
+    /// Empty requests are valid, e.g. some peers send them while probing the protocol, and are
+    /// answered with an empty [`PooledTransactions`] response. A non-empty response to an empty
+    /// request is a protocol violation, which
+    /// [`PooledTransactions::validate_strict_order`] reports.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Creates a request for all of the peer's announced hashes that are not in the `have` set.
+    ///
+    /// The order of the announced hashes is preserved.
+    pub fn request_missing(peer_hashes: Vec<H256>, have: &HashSet<H256>) -> Self {
+        GetPooledTransactions(peer_hashes.into_iter().filter(|hash| !have.contains(hash)).collect())
+    }
+
+    /// Creates a request for the given hashes, without duplicates.
+    ///
+    /// See [`GetPooledTransactions::dedup`].
+    pub fn from_iter_dedup<T: Into<H256>>(hashes: impl IntoIterator<Item = T>) -> Self {
+        let mut request = GetPooledTransactions(hashes.into_iter().map(Into::into).collect());
+        request.dedup();
+        request
+    }
+
+    /// Removes all duplicate hashes, keeping the first occurrence of each hash.
+    ///
+    /// The order of the remaining hashes is preserved, which matters because the bodies of the
+    /// response are expected in request order, see [`PooledTransactions::validate_strict_order`].
+    pub fn dedup(&mut self) {
+        let mut seen = HashSet::with_capacity(self.0.len());
+        self.0.retain(|hash| seen.insert(*hash));
+    }
+
+    /// Splits the given hashes into requests of at most `max_per_request` hashes each, keeping the
+    /// order of the hashes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_per_request` is zero.
+    pub fn chunked(hashes: Vec<H256>, max_per_request: usize) -> Vec<GetPooledTransactions> {
+        hashes.chunks(max_per_request).map(|chunk| GetPooledTransactions(chunk.to_vec())).collect()
+    }
+
+    /// Merges the given requests into as few requests as possible, each containing at most
+    /// `max_per_request` hashes.
+    ///
+    /// Duplicate hashes are removed, keeping the first occurrence, so the resulting hashes are in
+    /// the order they first appear in `requests`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_per_request` is zero.
+    pub fn coalesce_requests(
+        requests: Vec<GetPooledTransactions>,
+        max_per_request: usize,
+    ) -> Vec<GetPooledTransactions> {
+        let mut seen = HashSet::new();
+        let hashes = requests
+            .into_iter()
+            .flat_map(|request| request.0)
+            .filter(|hash| seen.insert(*hash))
+            .collect();
+        Self::chunked(hashes, max_per_request)
+    }
+
+    /// Returns `true` if the given hash is requested.
+    ///
+    /// This is a linear scan, see [`GetPooledTransactions::into_hash_set`] for repeated lookups.
+    pub fn contains(&self, hash: &H256) -> bool {
+        self.0.contains(hash)
+    }
+
+    /// Consumes the request and returns the set of requested hashes.
+    pub fn into_hash_set(self) -> HashSet<H256> {
+        self.0.into_iter().collect()
+    }
+
+    /// Returns the RLP encoded length of this request, without encoding it.
+    pub fn encoded_size(&self) -> usize {
+        let payload_length = self.0.len() * ENCODED_HASH_LEN;
+        length_of_length(payload_length) + payload_length
+    }
+}
+
 impl<T> From<Vec<T>> for GetPooledTransactions
 where
     T: Into<H256>,
@@ -31,6 +152,9 @@ where
 /// as the request's hashes. Hashes may be skipped, and the client should ensure that each body
 /// corresponds to a requested hash. Hashes may need to be re-requested if the bodies are not
 /// included in the response.
+///
+/// Bodies are encoded in the order they are stored, so a locally built response should be passed
+/// through [`PooledTransactions::reorder_to_request`] before it is sent.
 #[derive_arbitrary(rlp, 10)]
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -39,6 +163,282 @@ pub struct PooledTransactions(
     pub Vec<TransactionSigned>,
 );
 
+// === impl PooledTransactions ===
+
+impl PooledTransactions {
+    /// The `eth` protocol versions on which this message is valid.
+    ///
+    /// Which transaction types the message may carry depends on the version, see
+    /// [`PooledTransactions::validate_for_version`].
+    pub const SUPPORTED_VERSIONS: &'static [EthVersion] =
+        &[EthVersion::Eth66, EthVersion::Eth67, EthVersion::Eth68];
+
+    /// Decodes an RLP encoded response and checks every transaction against the given
+    /// [`TxValidation`] rules.
+    ///
+    /// Returns the index of the first transaction that violates a rule. Decoding through
+    /// [`Decodable`] stays lenient and accepts any structurally valid transaction.
+    pub fn decode_validated(
+        buf: &mut &[u8],
+        validation: TxValidation,
+    ) -> Result<Self, PooledTransactionsDecodeError> {
+        let txs = Self::decode(buf)?;
+        for (index, tx) in txs.0.iter().enumerate() {
+            validation.validate(tx).map_err(|kind| kind.at(index))?;
+        }
+        Ok(txs)
+    }
+
+    /// Returns an iterator over the transaction bodies.
+    pub fn iter(&self) -> std::slice::Iter<'_, TransactionSigned> {
+        self.0.iter()
+    }
+
+    /// Returns the number of transaction bodies.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no transaction bodies.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Checks that all transaction types are permitted on the given `eth` protocol version.
+    ///
+    /// All transaction types supported by [`TxType`] predate eth/66, so they are permitted on every
+    /// version.
+    pub fn validate_for_version(&self, version: EthVersion) -> Result<(), PooledTransactionsError> {
+        for tx in &self.0 {
+            let ty = tx.tx_type();
+            if !is_tx_type_supported(ty, version) {
+                return Err(PooledTransactionsError::UnsupportedTxType { ty: ty.into(), version })
+            }
+        }
+        Ok(())
+    }
+
+    /// Recovers the signers of all transactions, in order.
+    ///
+    /// Returns `None` if the signer of any transaction could not be recovered.
+    pub fn recover_signers(&self) -> Option<Vec<Address>> {
+        self.0.iter().map(|tx| tx.recover_signer()).collect()
+    }
+
+    /// Same as [`PooledTransactions::recover_signers`], but recovers the signers on the rayon
+    /// thread pool.
+    ///
+    /// The signers are recovered serially if the `rayon` feature is disabled or if there are fewer
+    /// than [`PARALLEL_SENDER_RECOVERY_THRESHOLD`] transactions.
+    pub fn recover_signers_par(&self) -> Option<Vec<Address>> {
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            if self.0.len() >= PARALLEL_SENDER_RECOVERY_THRESHOLD {
+                return self.0.par_iter().map(|tx| tx.recover_signer()).collect()
+            }
+        }
+        self.recover_signers()
+    }
+
+    /// Consumes the response and recovers the signers of all transactions, in order.
+    ///
+    /// Returns `None` if the signer of any transaction could not be recovered.
+    pub fn into_recovered(self) -> Option<Vec<TransactionSignedEcRecovered>> {
+        self.0.into_iter().map(|tx| tx.into_ecrecovered()).collect()
+    }
+
+    /// Returns the total RLP encoded size of the transaction bodies, per transaction type.
+    pub fn bytes_by_type(&self) -> HashMap<TxType, usize> {
+        let mut sizes = HashMap::new();
+        for tx in &self.0 {
+            *sizes.entry(tx.tx_type()).or_default() += tx.length();
+        }
+        sizes
+    }
+
+    /// Returns the RLP encoded length of this response, without encoding it.
+    pub fn encoded_size(&self) -> usize {
+        let payload_length = self.0.iter().map(Encodable::length).sum();
+        length_of_length(payload_length) + payload_length
+    }
+
+    /// Appends the RLP encoding of this response to the buffer, without clearing it.
+    ///
+    /// The buffer is grown at most once, so a pooled buffer can be reused across encodings without
+    /// allocating for every message.
+    pub fn encode_into(&self, buf: &mut BytesMut) {
+        buf.reserve(self.encoded_size());
+        self.encode(buf);
+    }
+
+    /// Packs the given transactions into a response whose encoded bodies do not exceed
+    /// `max_bytes`, returning the response and the transactions that did not fit.
+    ///
+    /// Transactions are packed greedily in order, using their RLP encoded length. The first
+    /// transaction is always included, even if it alone exceeds `max_bytes`.
+    pub fn from_transactions_capped(
+        mut txs: Vec<TransactionSigned>,
+        max_bytes: usize,
+    ) -> (Self, Vec<TransactionSigned>) {
+        let mut size = 0;
+        let split = txs
+            .iter()
+            .position(|tx| {
+                size += tx.length();
+                size > max_bytes
+            })
+            .map_or(txs.len(), |idx| idx.max(1));
+        let remaining = txs.split_off(split.min(txs.len()));
+        (PooledTransactions(txs), remaining)
+    }
+
+    /// Checks that the bodies in this response appear in the same order as the hashes of the given
+    /// request.
+    ///
+    /// Hashes may be skipped, but every body must correspond to a requested hash that comes after
+    /// the hash matched by the previous body. Returns the index of the first body that breaks this
+    /// rule, which is either out of order or was never requested.
+    ///
+    /// Responses with more bodies than requested hashes are rejected upfront.
+    ///
+    /// This is a single forward walk over the request, see
+    /// [`PooledTransactions::split_transactions_by_hashes_unordered`] for responses that may not
+    /// follow the request order.
+    pub fn validate_strict_order(
+        &self,
+        request: &GetPooledTransactions,
+    ) -> Result<(), PooledTransactionsError> {
+        self.ensure_not_larger_than(request)?;
+        let mut hashes = request.0.iter();
+        for (index, tx) in self.0.iter().enumerate() {
+            let hash = tx.hash_ref();
+            if !hashes.by_ref().any(|requested| requested == hash) {
+                return Err(PooledTransactionsError::OrderViolation { index })
+            }
+        }
+        Ok(())
+    }
+
+    /// Splits the response into the bodies that correspond to a hash of the given request and the
+    /// requested hashes that are missing from the response, regardless of the order of the bodies.
+    ///
+    /// Bodies that were already delivered are dropped. Matched bodies keep their response order and
+    /// missing hashes keep their request order. If the response contains bodies that were never
+    /// requested, an error carrying their hashes is returned instead.
+    ///
+    /// Unlike [`PooledTransactions::validate_strict_order`], which needs no allocations, this
+    /// builds a set of the requested hashes, so prefer the former if peers are expected to follow
+    /// the request order.
+    ///
+    /// Responses with more bodies than requested hashes are rejected upfront.
+    pub fn split_transactions_by_hashes_unordered(
+        self,
+        request: &GetPooledTransactions,
+    ) -> Result<(Vec<TransactionSigned>, Vec<H256>), PooledTransactionsError> {
+        self.ensure_not_larger_than(request)?;
+        let requested = request.0.iter().copied().collect::<HashSet<_>>();
+        let mut pending = requested.clone();
+        let mut matched = Vec::with_capacity(self.0.len());
+        let mut unmatched = Vec::new();
+        for tx in self.0 {
+            if pending.remove(tx.hash_ref()) {
+                matched.push(tx);
+            } else if !requested.contains(tx.hash_ref()) {
+                unmatched.push(tx.hash());
+            }
+        }
+
+        if !unmatched.is_empty() {
+            return Err(PooledTransactionsError::UnmatchedTransactions { hashes: unmatched })
+        }
+
+        let missing = request.0.iter().filter(|hash| pending.remove(hash)).copied().collect();
+        Ok((matched, missing))
+    }
+
+    /// Returns a follow-up request for the given requested hashes that have no body in this
+    /// response, or `None` if all of them were delivered.
+    ///
+    /// The requested order of the missing hashes is preserved.
+    pub fn missing_request(&self, requested: &[H256]) -> Option<GetPooledTransactions> {
+        let delivered = self.0.iter().map(|tx| tx.hash_ref()).collect::<HashSet<_>>();
+        let missing =
+            requested.iter().filter(|hash| !delivered.contains(hash)).copied().collect::<Vec<_>>();
+        (!missing.is_empty()).then_some(GetPooledTransactions(missing))
+    }
+
+    /// Sorts the bodies to match the positional order of the given request hashes.
+    ///
+    /// Bodies that were not requested are dropped, as are repeated bodies for the same hash. The
+    /// result satisfies [`PooledTransactions::validate_strict_order`] for the request.
+    pub fn reorder_to_request(&mut self, hashes: &[H256]) {
+        let mut positions = HashMap::with_capacity(hashes.len());
+        for (idx, hash) in hashes.iter().enumerate() {
+            positions.entry(*hash).or_insert(idx);
+        }
+        let mut bodies = std::mem::take(&mut self.0)
+            .into_iter()
+            .filter_map(|tx| positions.remove(tx.hash_ref()).map(|idx| (idx, tx)))
+            .collect::<Vec<_>>();
+        bodies.sort_unstable_by_key(|(idx, _)| *idx);
+        self.0 = bodies.into_iter().map(|(_, tx)| tx).collect();
+    }
+
+    /// Removes all duplicate transactions, keeping the first occurrence of each hash, and returns
+    /// the number of removed transactions.
+    ///
+    /// The order of the remaining transactions is preserved. A well-behaved peer never sends the
+    /// same transaction twice, so callers may penalize the peer if this removed anything.
+    pub fn dedup_by_hash(&mut self) -> usize {
+        let len = self.0.len();
+        let mut seen = HashSet::with_capacity(len);
+        self.0.retain(|tx| seen.insert(tx.hash()));
+        len - self.0.len()
+    }
+
+    /// Removes all transactions with structurally invalid signature values and returns their
+    /// hashes.
+    ///
+    /// A signature is rejected if `r` is not in `[1, n)` or `s` is not in `[1, n/2]`, see
+    /// [EIP-2](https://eips.ethereum.org/EIPS/eip-2). This is a cheap check that should run before
+    /// the expensive sender recovery. The y parity is not checked, since it is always well formed
+    /// once decoded.
+    pub fn reject_invalid_signature_params(&mut self) -> Vec<H256> {
+        self.reject_invalid_signature_params_with(TxValidation::default().with_low_s(true))
+    }
+
+    /// Same as [`Self::reject_invalid_signature_params`], but `s` only has to be in `[1, n/2]` if
+    /// [`TxValidation::require_low_s`] is set, otherwise it may be anywhere in `[1, n)`.
+    ///
+    /// The other rules of the [`TxValidation`] are not checked here.
+    pub fn reject_invalid_signature_params_with(&mut self, validation: TxValidation) -> Vec<H256> {
+        let mut rejected = Vec::new();
+        self.0.retain(|tx| {
+            let valid = has_valid_signature_params(tx.signature(), validation);
+            if !valid {
+                rejected.push(tx.hash());
+            }
+            valid
+        });
+        rejected
+    }
+
+    /// Returns an error if this response contains more bodies than the request has hashes.
+    fn ensure_not_larger_than(
+        &self,
+        request: &GetPooledTransactions,
+    ) -> Result<(), PooledTransactionsError> {
+        if self.0.len() > request.0.len() {
+            return Err(PooledTransactionsError::TooManyTransactions {
+                requested: request.0.len(),
+                received: self.0.len(),
+            })
+        }
+        Ok(())
+    }
+}
+
 impl From<Vec<TransactionSigned>> for PooledTransactions {
     fn from(txs: Vec<TransactionSigned>) -> Self {
         PooledTransactions(txs)
@@ -51,15 +451,528 @@ impl From<PooledTransactions> for Vec<TransactionSigned> {
     }
 }
 
+impl From<Transactions> for PooledTransactions {
+    fn from(txs: Transactions) -> Self {
+        PooledTransactions(txs.0)
+    }
+}
+
+impl From<PooledTransactions> for Transactions {
+    /// Converts the response into a broadcast.
+    ///
+    /// Transactions whose type may not be broadcast in full are stripped and must be announced
+    /// instead.
+    fn from(txs: PooledTransactions) -> Self {
+        Transactions(
+            txs.0.into_iter().filter(|tx| is_broadcastable_tx_type(tx.tx_type())).collect(),
+        )
+    }
+}
+
+impl Deref for PooledTransactions {
+    type Target = [TransactionSigned];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl IntoIterator for PooledTransactions {
+    type Item = TransactionSigned;
+    type IntoIter = std::vec::IntoIter<TransactionSigned>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PooledTransactions {
+    type Item = &'a TransactionSigned;
+    type IntoIter = std::slice::Iter<'a, TransactionSigned>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// A lazy decoder for the RLP encoding of [`PooledTransactions`].
+///
+/// Unlike decoding [`PooledTransactions`] directly, this decodes the transactions one at a time,
+/// so they can be processed and dropped without holding all of them in memory.
+#[derive(Debug)]
+pub struct PooledTransactionsDecoder<'a> {
+    /// The not yet decoded part of the list payload.
+    payload: &'a [u8],
+}
+
+impl<'a> PooledTransactionsDecoder<'a> {
+    /// Creates a decoder for the given RLP encoded [`PooledTransactions`], validating the outer
+    /// list header.
+    pub fn new(buf: &'a [u8]) -> Result<Self, DecodeError> {
+        let mut buf = buf;
+        let header = Header::decode(&mut buf)?;
+        if !header.list {
+            return Err(DecodeError::UnexpectedString)
+        }
+        if buf.len() < header.payload_length {
+            return Err(DecodeError::InputTooShort)
+        }
+        Ok(Self { payload: &buf[..header.payload_length] })
+    }
+}
+
+impl<'a> Iterator for PooledTransactionsDecoder<'a> {
+    type Item = Result<TransactionSigned, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.payload.is_empty() {
+            return None
+        }
+        let res = TransactionSigned::decode(&mut self.payload);
+        if res.is_err() {
+            // the remaining payload can't be decoded reliably after an error
+            self.payload = &[];
+        }
+        Some(res)
+    }
+}
+
+/// Rules that decoded transactions must satisfy, see [`PooledTransactions::decode_validated`].
+///
+/// The default accepts every structurally valid transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxValidation {
+    /// Whether the `s` value of the signature must be in the lower half of the curve order, as
+    /// required by [EIP-2](https://eips.ethereum.org/EIPS/eip-2).
+    pub require_low_s: bool,
+    /// Whether the transaction must commit to a chain id, which rules out legacy transactions
+    /// without [EIP-155](https://eips.ethereum.org/EIPS/eip-155) replay protection.
+    pub require_chain_id: bool,
+}
+
+// === impl TxValidation ===
+
+impl TxValidation {
+    /// Returns the rules that require both a low `s` value and a chain id.
+    pub const fn strict() -> Self {
+        Self { require_low_s: true, require_chain_id: true }
+    }
+
+    /// Sets whether the `s` value of the signature must be low.
+    pub const fn with_low_s(mut self, require_low_s: bool) -> Self {
+        self.require_low_s = require_low_s;
+        self
+    }
+
+    /// Sets whether the transaction must commit to a chain id.
+    pub const fn with_chain_id(mut self, require_chain_id: bool) -> Self {
+        self.require_chain_id = require_chain_id;
+        self
+    }
+
+    /// Returns `true` if the `s` value of the signature is allowed by [`Self::require_low_s`].
+    fn allows_s(&self, signature: &Signature) -> bool {
+        !self.require_low_s || signature.s <= SECP256K1N_HALF
+    }
+
+    /// Checks the given transaction against these rules.
+    fn validate(&self, tx: &TransactionSigned) -> Result<(), TxValidationErrorKind> {
+        if !self.allows_s(tx.signature()) {
+            return Err(TxValidationErrorKind::HighS)
+        }
+        if self.require_chain_id && tx.chain_id().is_none() {
+            return Err(TxValidationErrorKind::MissingChainId)
+        }
+        Ok(())
+    }
+}
+
+/// The rule of a [`TxValidation`] that a transaction violates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TxValidationErrorKind {
+    HighS,
+    MissingChainId,
+}
+
+impl TxValidationErrorKind {
+    /// Attaches the index of the offending transaction.
+    fn at(self, index: usize) -> PooledTransactionsDecodeError {
+        match self {
+            TxValidationErrorKind::HighS => PooledTransactionsDecodeError::HighS { index },
+            TxValidationErrorKind::MissingChainId => {
+                PooledTransactionsDecodeError::MissingChainId { index }
+            }
+        }
+    }
+}
+
+/// Returns `true` if transactions of the given type are permitted on the given `eth` protocol
+/// version.
+///
+/// All types of [`TxType`] predate eth/66. Types introduced by later versions, like blob
+/// transactions on eth/68, must be restricted here once they are added.
+fn is_tx_type_supported(ty: TxType, _version: EthVersion) -> bool {
+    match ty {
+        TxType::Legacy | TxType::EIP2930 | TxType::EIP1559 => true,
+    }
+}
+
+/// Returns `true` if transactions of the given type may be broadcast in full.
+///
+/// All types of [`TxType`] may be broadcast. Blob transactions must be excluded here once they are
+/// added, since they are only ever announced.
+fn is_broadcastable_tx_type(ty: TxType) -> bool {
+    match ty {
+        TxType::Legacy | TxType::EIP2930 | TxType::EIP1559 => true,
+    }
+}
+
+/// Returns `true` if `r` and `s` of the signature are within the valid range, with the upper
+/// bound of `s` determined by [`TxValidation::require_low_s`].
+fn has_valid_signature_params(signature: &Signature, validation: TxValidation) -> bool {
+    !signature.r.is_zero() &&
+        signature.r < SECP256K1N &&
+        !signature.s.is_zero() &&
+        signature.s < SECP256K1N &&
+        validation.allows_s(signature)
+}
+
+/// Errors when matching a [`PooledTransactions`] response against its [`GetPooledTransactions`]
+/// request.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PooledTransactionsError {
+    /// The response contains more bodies than hashes were requested.
+    #[error("received {received} pooled transactions for {requested} requested hashes")]
+    TooManyTransactions {
+        /// The number of requested hashes.
+        requested: usize,
+        /// The number of bodies in the response.
+        received: usize,
+    },
+    /// A body in the response does not follow the order of the request.
+    #[error("pooled transaction at index {index} violates the request order")]
+    OrderViolation {
+        /// The index of the first body in the response that violates the request order.
+        index: usize,
+    },
+    /// The response contains a transaction type that is not permitted on the negotiated version.
+    #[error("transaction type {ty} is not supported on {version:?}")]
+    UnsupportedTxType {
+        /// The id of the transaction type.
+        ty: u8,
+        /// The negotiated `eth` protocol version.
+        version: EthVersion,
+    },
+    /// The response contains bodies that were not requested.
+    #[error("received {} unrequested pooled transactions", hashes.len())]
+    UnmatchedTransactions {
+        /// The hashes of the bodies that do not correspond to any requested hash.
+        hashes: Vec<H256>,
+    },
+}
+
+/// Errors when decoding a [`PooledTransactions`] response with
+/// [`PooledTransactions::decode_validated`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PooledTransactionsDecodeError {
+    /// The response is not a valid RLP encoded list of transactions.
+    #[error(transparent)]
+    Rlp(#[from] DecodeError),
+    /// A transaction has a signature with an `s` value in the upper half of the curve order.
+    #[error("pooled transaction at index {index} has a high s value")]
+    HighS {
+        /// The index of the offending transaction in the response.
+        index: usize,
+    },
+    /// A transaction does not commit to a chain id.
+    #[error("pooled transaction at index {index} has no chain id")]
+    MissingChainId {
+        /// The index of the offending transaction in the response.
+        index: usize,
+    },
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{message::RequestPair, GetPooledTransactions, PooledTransactions};
+    use super::PARALLEL_SENDER_RECOVERY_THRESHOLD;
+    use crate::{
+        message::RequestPair, EthVersion, GetPooledTransactions, PooledTransactions,
+        PooledTransactionsDecodeError, PooledTransactionsDecoder, PooledTransactionsError,
+        Transactions, TxValidation,
+    };
+    use bytes::BytesMut;
     use hex_literal::hex;
     use reth_primitives::{
-        hex, Signature, Transaction, TransactionKind, TransactionSigned, TxEip1559, TxLegacy, U256,
+        hex, Address, Signature, Transaction, TransactionKind, TransactionSigned, TxEip1559,
+        TxEip2930, TxLegacy, TxType, H256, SECP256K1N, SECP256K1N_HALF, U256,
     };
-    use reth_rlp::{Decodable, Encodable};
-    use std::str::FromStr;
+    use reth_rlp::{Decodable, DecodeError, Encodable};
+    use std::{collections::HashSet, str::FromStr};
+
+    /// Returns a legacy transaction with the given nonce, so that every nonce yields a distinct
+    /// hash.
+    fn legacy_tx(nonce: u64) -> TransactionSigned {
+        TransactionSigned::from_transaction_and_signature(
+            Transaction::Legacy(TxLegacy { nonce, ..Default::default() }),
+            Signature::default(),
+        )
+    }
+
+    /// Returns an EIP-1559 transaction with the given nonce.
+    fn eip1559_tx(nonce: u64) -> TransactionSigned {
+        TransactionSigned::from_transaction_and_signature(
+            Transaction::Eip1559(TxEip1559 {
+                chain_id: 1,
+                nonce,
+                max_fee_per_gas: 0x4a817c800,
+                max_priority_fee_per_gas: 0x3b9aca00,
+                input: hex!("deadbeef").into(),
+                ..Default::default()
+            }),
+            Signature::default(),
+        )
+    }
+
+    #[test]
+    fn iterate_pooled_transactions() {
+        let response = PooledTransactions((1..=4).map(legacy_tx).collect());
+        assert_eq!(response.len(), 4);
+        assert!(!response.is_empty());
+        assert!(PooledTransactions::default().is_empty());
+
+        assert_eq!(response.iter().map(|tx| tx.nonce()).sum::<u64>(), 10);
+        assert_eq!((&response).into_iter().map(|tx| tx.nonce()).sum::<u64>(), 10);
+        assert_eq!(response[1..].iter().map(|tx| tx.nonce()).sum::<u64>(), 9);
+        assert_eq!(response.into_iter().map(|tx| tx.nonce()).sum::<u64>(), 10);
+    }
+
+    #[test]
+    fn reject_out_of_range_signatures() {
+        let tx_with_signature = |nonce, r: U256, s: U256| {
+            TransactionSigned::from_transaction_and_signature(
+                Transaction::Legacy(TxLegacy { nonce, ..Default::default() }),
+                Signature { r, s, odd_y_parity: false },
+            )
+        };
+        let valid = tx_with_signature(0, U256::from(1), SECP256K1N_HALF);
+        let high_s = tx_with_signature(1, U256::from(1), SECP256K1N_HALF + U256::from(1));
+        let zero_r = tx_with_signature(2, U256::ZERO, U256::from(1));
+        let overflowing_s = tx_with_signature(3, U256::from(1), SECP256K1N);
+        let txs = vec![high_s.clone(), valid.clone(), zero_r.clone(), overflowing_s.clone()];
+
+        let mut response = PooledTransactions(txs.clone());
+        let rejected = response.reject_invalid_signature_params();
+        assert_eq!(rejected, vec![high_s.hash(), zero_r.hash(), overflowing_s.hash()]);
+        assert_eq!(response, PooledTransactions(vec![valid.clone()]));
+
+        let mut response = PooledTransactions(txs.clone());
+        let rejected = response.reject_invalid_signature_params_with(TxValidation::default());
+        assert_eq!(rejected, vec![zero_r.hash(), overflowing_s.hash()]);
+        assert_eq!(response, PooledTransactions(vec![high_s, valid.clone()]));
+
+        let mut response = PooledTransactions(txs);
+        let rejected = response.reject_invalid_signature_params_with(TxValidation::strict());
+        assert_eq!(rejected.len(), 3);
+        assert_eq!(response, PooledTransactions(vec![valid]));
+    }
+
+    #[test]
+    fn decode_validated_rejects_high_s() {
+        let high_s = TransactionSigned::from_transaction_and_signature(
+            eip1559_tx(1).transaction,
+            Signature { r: U256::from(1), s: SECP256K1N_HALF + U256::from(1), odd_y_parity: false },
+        );
+        let response = PooledTransactions(vec![eip1559_tx(0), high_s]);
+        let mut encoded = Vec::new();
+        response.encode(&mut encoded);
+
+        let lenient =
+            PooledTransactions::decode_validated(&mut &encoded[..], TxValidation::default())
+                .unwrap();
+        assert_eq!(lenient, response);
+        assert_eq!(PooledTransactions::decode(&mut &encoded[..]).unwrap(), response);
+
+        assert_eq!(
+            PooledTransactions::decode_validated(&mut &encoded[..], TxValidation::strict()),
+            Err(PooledTransactionsDecodeError::HighS { index: 1 })
+        );
+    }
+
+    #[test]
+    fn decode_validated_requires_chain_id() {
+        let response = PooledTransactions(vec![eip1559_tx(0), legacy_tx(1)]);
+        let mut encoded = Vec::new();
+        response.encode(&mut encoded);
+
+        let validation = TxValidation::default().with_chain_id(true);
+        assert_eq!(
+            PooledTransactions::decode_validated(&mut &encoded[..], validation),
+            Err(PooledTransactionsDecodeError::MissingChainId { index: 1 })
+        );
+        assert_eq!(
+            PooledTransactions::decode_validated(
+                &mut &encoded[..],
+                validation.with_chain_id(false)
+            ),
+            Ok(response)
+        );
+    }
+
+    #[test]
+    fn encode_into_appends_to_buffer() {
+        let first = PooledTransactions(vec![legacy_tx(0), eip1559_tx(1)]);
+        let second = PooledTransactions(vec![eip1559_tx(2)]);
+
+        let mut buf = BytesMut::new();
+        first.encode_into(&mut buf);
+        assert_eq!(buf.len(), first.encoded_size());
+        second.encode_into(&mut buf);
+
+        let mut expected = Vec::new();
+        first.encode(&mut expected);
+        second.encode(&mut expected);
+        assert_eq!(&buf[..], &expected[..]);
+
+        let mut decoded = &buf[..];
+        assert_eq!(PooledTransactions::decode(&mut decoded).unwrap(), first);
+        assert_eq!(PooledTransactions::decode(&mut decoded).unwrap(), second);
+        assert!(decoded.is_empty());
+    }
+
+    #[test]
+    fn request_missing_hashes() {
+        let hashes = (0..5).map(|i| legacy_tx(i).hash()).collect::<Vec<_>>();
+        let have = HashSet::from([hashes[1], hashes[3], legacy_tx(10).hash()]);
+
+        let request = GetPooledTransactions::request_missing(hashes.clone(), &have);
+        assert_eq!(request, GetPooledTransactions(vec![hashes[0], hashes[2], hashes[4]]));
+    }
+
+    #[test]
+    fn dedup_hashes_in_order() {
+        let hashes = (0..4).map(H256::from_low_u64_be).collect::<Vec<_>>();
+        let with_duplicates =
+            vec![hashes[2], hashes[0], hashes[2], hashes[1], hashes[0], hashes[3], hashes[2]];
+        let expected = GetPooledTransactions(vec![hashes[2], hashes[0], hashes[1], hashes[3]]);
+
+        let mut request = GetPooledTransactions(with_duplicates.clone());
+        request.dedup();
+        assert_eq!(request, expected);
+        assert_eq!(GetPooledTransactions::from_iter_dedup(with_duplicates), expected);
+    }
+
+    #[test]
+    fn chunk_hashes_into_requests() {
+        let hashes = (0..2500).map(H256::from_low_u64_be).collect::<Vec<_>>();
+
+        let requests = GetPooledTransactions::chunked(hashes.clone(), 256);
+        assert_eq!(requests.len(), 10);
+        assert!(requests[..9].iter().all(|request| request.0.len() == 256));
+        assert_eq!(requests[9].0.len(), 2500 - 9 * 256);
+        assert_eq!(requests.into_iter().flat_map(|request| request.0).collect::<Vec<_>>(), hashes);
+    }
+
+    #[test]
+    fn coalesce_overlapping_requests() {
+        let hashes = (0..6).map(|i| legacy_tx(i).hash()).collect::<Vec<_>>();
+        let requests = vec![
+            GetPooledTransactions(vec![hashes[0], hashes[1]]),
+            GetPooledTransactions(vec![hashes[1], hashes[2], hashes[3]]),
+            GetPooledTransactions(vec![hashes[3], hashes[0]]),
+            GetPooledTransactions(vec![hashes[4], hashes[5], hashes[2]]),
+        ];
+
+        let coalesced = GetPooledTransactions::coalesce_requests(requests, 4);
+        assert_eq!(
+            coalesced,
+            vec![
+                GetPooledTransactions(vec![hashes[0], hashes[1], hashes[2], hashes[3]]),
+                GetPooledTransactions(vec![hashes[4], hashes[5]]),
+            ]
+        );
+
+        assert!(GetPooledTransactions::coalesce_requests(vec![], 4).is_empty());
+    }
+
+    #[test]
+    fn strict_order_allows_skipped_hashes() {
+        let txs = (0..4).map(legacy_tx).collect::<Vec<_>>();
+        let request = GetPooledTransactions(txs.iter().map(|tx| tx.hash()).collect());
+
+        let response = PooledTransactions(vec![txs[0].clone(), txs[2].clone(), txs[3].clone()]);
+        assert_eq!(response.validate_strict_order(&request), Ok(()));
+
+        let response = PooledTransactions(vec![]);
+        assert_eq!(response.validate_strict_order(&request), Ok(()));
+    }
+
+    #[test]
+    fn split_permuted_response_by_hashes() {
+        let txs = (0..4).map(legacy_tx).collect::<Vec<_>>();
+        let request = GetPooledTransactions(txs.iter().map(|tx| tx.hash()).collect());
+
+        let response = PooledTransactions(vec![
+            txs[3].clone(),
+            txs[0].clone(),
+            txs[3].clone(),
+            txs[1].clone(),
+        ]);
+        assert!(response.validate_strict_order(&request).is_err());
+
+        let (matched, missing) = response.split_transactions_by_hashes_unordered(&request).unwrap();
+        assert_eq!(matched, vec![txs[3].clone(), txs[0].clone(), txs[1].clone()]);
+        assert_eq!(missing, vec![txs[2].hash()]);
+    }
+
+    #[test]
+    fn split_reports_unmatched_transactions() {
+        let txs = (0..4).map(legacy_tx).collect::<Vec<_>>();
+        let request = GetPooledTransactions(txs.iter().map(|tx| tx.hash()).collect());
+        let bogus = vec![legacy_tx(10), legacy_tx(11)];
+
+        let response = PooledTransactions(vec![
+            txs[0].clone(),
+            bogus[0].clone(),
+            txs[1].clone(),
+            bogus[1].clone(),
+        ]);
+        assert_eq!(
+            response.split_transactions_by_hashes_unordered(&request),
+            Err(PooledTransactionsError::UnmatchedTransactions {
+                hashes: bogus.iter().map(|tx| tx.hash()).collect()
+            })
+        );
+    }
+
+    #[test]
+    fn strict_order_rejects_reordering() {
+        let txs = (0..4).map(legacy_tx).collect::<Vec<_>>();
+        let request = GetPooledTransactions(txs.iter().map(|tx| tx.hash()).collect());
+
+        let response = PooledTransactions(vec![txs[0].clone(), txs[3].clone(), txs[1].clone()]);
+        assert_eq!(
+            response.validate_strict_order(&request),
+            Err(PooledTransactionsError::OrderViolation { index: 2 })
+        );
+
+        // a body that was never requested is a violation as well
+        let response = PooledTransactions(vec![txs[1].clone(), legacy_tx(10)]);
+        assert_eq!(
+            response.validate_strict_order(&request),
+            Err(PooledTransactionsError::OrderViolation { index: 1 })
+        );
+    }
+
+    #[test]
+    fn reject_response_larger_than_request() {
+        let txs = (0..3).map(legacy_tx).collect::<Vec<_>>();
+        let request = GetPooledTransactions(txs[..2].iter().map(|tx| tx.hash()).collect());
+        let response = PooledTransactions(txs);
+
+        let err = PooledTransactionsError::TooManyTransactions { requested: 2, received: 3 };
+        assert_eq!(response.validate_strict_order(&request), Err(err.clone()));
+        assert_eq!(response.split_transactions_by_hashes_unordered(&request), Err(err));
+    }
 
     #[test]
     // Test vector from: https://eips.ethereum.org/EIPS/eip-2481
@@ -94,6 +1007,26 @@ mod test {
         );
     }
 
+    #[test]
+    fn roundtrip_empty_pooled_transactions() {
+        let request = RequestPair { request_id: 1111, message: GetPooledTransactions::default() };
+        let mut data = vec![];
+        request.encode(&mut data);
+        assert_eq!(data, hex!("c4820457c0"));
+        let decoded = RequestPair::<GetPooledTransactions>::decode(&mut &data[..]).unwrap();
+        assert_eq!(decoded, request);
+        assert!(decoded.message.is_empty());
+
+        let response = RequestPair { request_id: 1111, message: PooledTransactions::default() };
+        let mut data = vec![];
+        response.encode(&mut data);
+        assert_eq!(data, hex!("c4820457c0"));
+        assert_eq!(RequestPair::<PooledTransactions>::decode(&mut &data[..]).unwrap(), response);
+
+        let violation = PooledTransactions(vec![legacy_tx(0)]);
+        assert!(violation.validate_strict_order(&request.message).is_err());
+    }
+
     #[test]
     // Test vector from: https://eips.ethereum.org/EIPS/eip-2481
     fn encode_pooled_transactions() {
@@ -220,6 +1153,49 @@ mod test {
         assert_eq!(request, expected);
     }
 
+    #[test]
+    // Test vector from: https://eips.ethereum.org/EIPS/eip-2481
+    fn recover_pooled_transactions_signers() {
+        let data = hex!("f8d7820457f8d2f867088504a817c8088302e2489435353535353535353535353535353535353535358202008025a064b1702d9298fee62dfeccc57d322a463ad55ca201256d01f62b45b2e1c21c12a064b1702d9298fee62dfeccc57d322a463ad55ca201256d01f62b45b2e1c21c10f867098504a817c809830334509435353535353535353535353535353535353535358202d98025a052f8f61201b2b11a78d6e866abc9c3db2ae8631fa656bfe5cb53668255367afba052f8f61201b2b11a78d6e866abc9c3db2ae8631fa656bfe5cb53668255367afb");
+        let response = RequestPair::<PooledTransactions>::decode(&mut &data[..]).unwrap().message;
+        let expected: Vec<Address> = vec![
+            hex!("9bddad43f934d313c2b79ca28a432dd2b7281029").into(),
+            hex!("3c24d7329e92f84f08556ceb6df1cdb0104ca49f").into(),
+        ];
+
+        assert_eq!(response.recover_signers(), Some(expected.clone()));
+
+        let recovered = response.clone().into_recovered().unwrap();
+        assert_eq!(recovered.iter().map(|tx| tx.signer()).collect::<Vec<_>>(), expected);
+        assert_eq!(
+            recovered.into_iter().map(TransactionSigned::from).collect::<Vec<_>>(),
+            response.0
+        );
+
+        // recover a batch that is large enough to be recovered in parallel
+        let batch = PooledTransactions(
+            response
+                .0
+                .iter()
+                .cycle()
+                .take(2 * PARALLEL_SENDER_RECOVERY_THRESHOLD + 1)
+                .cloned()
+                .collect(),
+        );
+        assert_eq!(response.recover_signers_par(), Some(expected));
+        assert_eq!(batch.recover_signers_par(), batch.recover_signers());
+        assert!(batch.recover_signers().is_some());
+
+        // a zero signature can not be recovered
+        let mut response = response;
+        response.0.push(legacy_tx(0));
+        assert_eq!(response.recover_signers(), None);
+        let mut batch = batch;
+        batch.0.push(legacy_tx(0));
+        assert_eq!(batch.recover_signers_par(), None);
+        assert!(response.into_recovered().is_none());
+    }
+
     #[test]
     fn decode_pooled_transactions_network() {
         let data = hex!("f9022980f90225f8650f84832156008287fb94cf7f9e66af820a19257a2108375b180b0ec491678204d2802ca035b7bfeb9ad9ece2cbafaaf8e202e706b4cfaeb233f46198f00b44d4a566a981a0612638fb29427ca33b9a3be2a0a561beecfe0269655be160d35e72d366a6a860b87502f872041a8459682f008459682f0d8252089461815774383099e24810ab832a5b2a5425c154d58829a2241af62c000080c001a059e6b67f48fb32e7e570dfb11e042b5ad2e55e3ce3ce9cd989c7e06e07feeafda0016b83f4f980694ed2eee4d10667242b1f40dc406901b34125b008d334d47469f86b0384773594008398968094d3e8763675e4c425df46cc3b5c0f6cbdac39604687038d7ea4c68000802ba0ce6834447c0a4193c40382e6c57ae33b241379c5418caac9cdc18d786fd12071a03ca3ae86580e94550d7c071e3a02eadb5a77830947c9225165cf9100901bee88f86b01843b9aca00830186a094d3e8763675e4c425df46cc3b5c0f6cbdac3960468702769bb01b2a00802ba0e24d8bd32ad906d6f8b8d7741e08d1959df021698b19ee232feba15361587d0aa05406ad177223213df262cb66ccbb2f46bfdccfdfbbb5ffdda9e2c02d977631daf86b02843b9aca00830186a094d3e8763675e4c425df46cc3b5c0f6cbdac39604687038d7ea4c68000802ba00eb96ca19e8a77102767a41fc85a36afd5c61ccb09911cec5d3e86e193d9c5aea03a456401896b1b6055311536bf00a718568c744d8c1f9df59879e8350220ca18");
@@ -506,4 +1482,193 @@ mod test {
         assert_eq!(encoded_str.len(), expected_str.len());
         assert_eq!(encoded_str, expected_str);
     }
+
+    #[test]
+    fn validate_tx_types_for_version() {
+        let eip2930_tx = TransactionSigned::from_transaction_and_signature(
+            Transaction::Eip2930(TxEip2930 { chain_id: 1, ..Default::default() }),
+            Signature::default(),
+        );
+        let response = PooledTransactions(vec![legacy_tx(0), eip2930_tx, eip1559_tx(1)]);
+        for version in [EthVersion::Eth66, EthVersion::Eth67, EthVersion::Eth68] {
+            assert_eq!(response.validate_for_version(version), Ok(()));
+        }
+    }
+
+    #[test]
+    fn request_contains_hash() {
+        let request = GetPooledTransactions((1..=3).map(H256::from_low_u64_be).collect());
+        let (present, absent) = (H256::from_low_u64_be(2), H256::from_low_u64_be(4));
+        assert!(request.contains(&present));
+        assert!(!request.contains(&absent));
+
+        let hashes = request.into_hash_set();
+        assert_eq!(hashes.len(), 3);
+        assert!(hashes.contains(&present));
+        assert!(!hashes.contains(&absent));
+    }
+
+    #[test]
+    fn encoded_size_matches_encoding() {
+        for len in [0, 1, 2, 100] {
+            let request = GetPooledTransactions((0..len).map(H256::from_low_u64_be).collect());
+            let mut buf = Vec::new();
+            request.encode(&mut buf);
+            assert_eq!(request.encoded_size(), buf.len());
+
+            let response = PooledTransactions((0..len).map(eip1559_tx).collect());
+            let mut buf = Vec::new();
+            response.encode(&mut buf);
+            assert_eq!(response.encoded_size(), buf.len());
+        }
+    }
+
+    #[test]
+    fn follow_up_request_for_missing_hashes() {
+        let txs = (0..3).map(legacy_tx).collect::<Vec<_>>();
+        let requested = txs.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+
+        let response = PooledTransactions(vec![txs[1].clone()]);
+        assert_eq!(
+            response.missing_request(&requested),
+            Some(GetPooledTransactions(vec![requested[0], requested[2]]))
+        );
+
+        let response = PooledTransactions(txs);
+        assert_eq!(response.missing_request(&requested), None);
+    }
+
+    #[test]
+    fn reorder_shuffled_response_to_request() {
+        let txs = (0..5).map(legacy_tx).collect::<Vec<_>>();
+        let request = GetPooledTransactions(txs.iter().map(|tx| tx.hash()).collect());
+
+        let mut response = PooledTransactions(vec![
+            txs[3].clone(),
+            txs[0].clone(),
+            legacy_tx(42),
+            txs[4].clone(),
+            txs[1].clone(),
+            txs[0].clone(),
+        ]);
+        assert!(response.validate_strict_order(&request).is_err());
+
+        response.reorder_to_request(&request.0);
+        assert_eq!(
+            response,
+            PooledTransactions(vec![
+                txs[0].clone(),
+                txs[1].clone(),
+                txs[3].clone(),
+                txs[4].clone()
+            ])
+        );
+        assert_eq!(response.validate_strict_order(&request), Ok(()));
+    }
+
+    #[test]
+    fn dedup_transactions_by_hash() {
+        let txs = (0..3).map(legacy_tx).collect::<Vec<_>>();
+        let mut response = PooledTransactions(vec![
+            txs[1].clone(),
+            txs[0].clone(),
+            txs[1].clone(),
+            txs[2].clone(),
+        ]);
+
+        assert_eq!(response.dedup_by_hash(), 1);
+        assert_eq!(
+            response,
+            PooledTransactions(vec![txs[1].clone(), txs[0].clone(), txs[2].clone()])
+        );
+        assert_eq!(response.dedup_by_hash(), 0);
+    }
+
+    #[test]
+    fn convert_broadcast_and_pooled_transactions() {
+        let eip2930_tx = TransactionSigned::from_transaction_and_signature(
+            Transaction::Eip2930(TxEip2930 { chain_id: 1, nonce: 2, ..Default::default() }),
+            Signature::default(),
+        );
+        // every transaction type that can be decoded is broadcast in full
+        let txs = vec![legacy_tx(0), eip1559_tx(1), eip2930_tx];
+        let hashes = txs.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+
+        let pooled = PooledTransactions::from(Transactions(txs.clone()));
+        assert_eq!(pooled, PooledTransactions(txs.clone()));
+
+        let broadcast = Transactions::from(pooled);
+        assert_eq!(broadcast, Transactions(txs));
+        assert_eq!(broadcast.into_hashes(), hashes);
+    }
+
+    #[test]
+    fn bytes_by_transaction_type() {
+        let txs = vec![legacy_tx(0), eip1559_tx(1), legacy_tx(2), eip1559_tx(3), eip1559_tx(4)];
+        let response = PooledTransactions(txs.clone());
+
+        let sizes = response.bytes_by_type();
+        assert_eq!(sizes.len(), 2);
+        assert_eq!(sizes[&TxType::Legacy], txs[0].length() + txs[2].length());
+        assert_eq!(sizes[&TxType::EIP1559], txs[1].length() + txs[3].length() + txs[4].length());
+        assert_eq!(sizes.values().sum::<usize>(), txs.iter().map(|tx| tx.length()).sum::<usize>());
+
+        assert!(PooledTransactions::default().bytes_by_type().is_empty());
+    }
+
+    #[test]
+    fn capped_response_matches_encoded_length() {
+        let txs = vec![legacy_tx(0), eip1559_tx(1), legacy_tx(2), eip1559_tx(3)];
+        let max_bytes = txs[..3].iter().map(|tx| tx.length()).sum::<usize>();
+
+        let (response, remaining) =
+            PooledTransactions::from_transactions_capped(txs.clone(), max_bytes);
+        assert_eq!(response.0, txs[..3]);
+        assert_eq!(remaining, txs[3..]);
+
+        let mut buf = Vec::new();
+        response.0.iter().for_each(|tx| tx.encode(&mut buf));
+        assert_eq!(buf.len(), max_bytes);
+
+        let (response, remaining) =
+            PooledTransactions::from_transactions_capped(txs.clone(), max_bytes - 1);
+        assert_eq!(response.0, txs[..2]);
+        assert_eq!(remaining, txs[2..]);
+    }
+
+    #[test]
+    fn capped_response_includes_first_transaction() {
+        let txs = vec![eip1559_tx(0), legacy_tx(1)];
+
+        let (response, remaining) = PooledTransactions::from_transactions_capped(txs.clone(), 1);
+        assert_eq!(response.0, txs[..1]);
+        assert_eq!(remaining, txs[1..]);
+
+        let (response, remaining) = PooledTransactions::from_transactions_capped(vec![], 1);
+        assert!(response.0.is_empty());
+        assert!(remaining.is_empty());
+    }
+
+    #[test]
+    fn lazy_decode_pooled_transactions() {
+        // the `PooledTransactions` frame of the `decode_pooled_transactions_network` test, without
+        // the request pair
+        let data = hex!("f90225f8650f84832156008287fb94cf7f9e66af820a19257a2108375b180b0ec491678204d2802ca035b7bfeb9ad9ece2cbafaaf8e202e706b4cfaeb233f46198f00b44d4a566a981a0612638fb29427ca33b9a3be2a0a561beecfe0269655be160d35e72d366a6a860b87502f872041a8459682f008459682f0d8252089461815774383099e24810ab832a5b2a5425c154d58829a2241af62c000080c001a059e6b67f48fb32e7e570dfb11e042b5ad2e55e3ce3ce9cd989c7e06e07feeafda0016b83f4f980694ed2eee4d10667242b1f40dc406901b34125b008d334d47469f86b0384773594008398968094d3e8763675e4c425df46cc3b5c0f6cbdac39604687038d7ea4c68000802ba0ce6834447c0a4193c40382e6c57ae33b241379c5418caac9cdc18d786fd12071a03ca3ae86580e94550d7c071e3a02eadb5a77830947c9225165cf9100901bee88f86b01843b9aca00830186a094d3e8763675e4c425df46cc3b5c0f6cbdac3960468702769bb01b2a00802ba0e24d8bd32ad906d6f8b8d7741e08d1959df021698b19ee232feba15361587d0aa05406ad177223213df262cb66ccbb2f46bfdccfdfbbb5ffdda9e2c02d977631daf86b02843b9aca00830186a094d3e8763675e4c425df46cc3b5c0f6cbdac39604687038d7ea4c68000802ba00eb96ca19e8a77102767a41fc85a36afd5c61ccb09911cec5d3e86e193d9c5aea03a456401896b1b6055311536bf00a718568c744d8c1f9df59879e8350220ca18");
+        let eager = PooledTransactions::decode(&mut &data[..]).unwrap();
+        assert_eq!(eager.len(), 5);
+
+        let lazy = PooledTransactionsDecoder::new(&data[..])
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(lazy, eager.0);
+
+        assert_eq!(
+            PooledTransactionsDecoder::new(&hex!("820102")[..]).unwrap_err(),
+            DecodeError::UnexpectedString
+        );
+        let mut decoder = PooledTransactionsDecoder::new(&hex!("c20102")[..]).unwrap();
+        assert!(decoder.next().unwrap().is_err());
+        assert!(decoder.next().is_none());
+    }
 }
@@ -165,6 +165,12 @@ where
         request: GetPooledTransactions,
         response: oneshot::Sender<RequestResult<PooledTransactions>>,
     ) {
+        if request.is_empty() {
+            // nothing to look up in the pool
+            let _ = response.send(Ok(PooledTransactions::default()));
+            return
+        }
+
         if let Some(peer) = self.peers.get_mut(&peer_id) {
             let transactions = self
                 .pool
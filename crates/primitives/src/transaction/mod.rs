@@ -12,7 +12,7 @@ use reth_rlp::{
     length_of_length, Decodable, DecodeError, Encodable, Header, EMPTY_LIST_CODE, EMPTY_STRING_CODE,
 };
 use serde::{Deserialize, Serialize};
-pub use signature::Signature;
+pub use signature::{Signature, SECP256K1N, SECP256K1N_HALF};
 pub use tx_type::{TxType, EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID, LEGACY_TX_TYPE_ID};
 
 mod access_list;
@@ -14,7 +14,9 @@ pub const EIP1559_TX_TYPE_ID: u8 = 2;
 
 /// Transaction Type
 #[derive_arbitrary(compact)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
 pub enum TxType {
     /// Legacy transaction pre EIP-2929
     #[default]
@@ -4,7 +4,7 @@ use crate::tracing::{
     types::{CallTraceNode, CallTraceStepStackItem},
     TracingInspectorConfig,
 };
-use reth_primitives::{Address, H256};
+use reth_primitives::{constants::SELECTOR_LEN, hex, Address, H256};
 use reth_rpc_types::trace::geth::*;
 use std::collections::{BTreeMap, HashMap, VecDeque};
 
@@ -144,4 +144,30 @@ impl GethTraceBuilder {
             }
         }
     }
+
+    /// Generate a geth-style trace for the 4byte tracer.
+    ///
+    /// This is a histogram of the function selectors and calldata sizes of all recorded calls,
+    /// keyed by `SELECTOR-CALLDATASIZE`. Like geth's `4byteTracer`, calls to precompiles are
+    /// skipped.
+    ///
+    /// Unlike the standalone [FourByteInspector](crate::tracing::FourByteInspector), this is built
+    /// from the calls already recorded by the
+    /// [TracingInspector](crate::tracing::TracingInspector), so a single execution can serve the
+    /// 4byte tracer alongside the other geth tracers.
+    pub fn geth_four_byte_traces(&self) -> FourByteFrame {
+        let mut histogram = BTreeMap::new();
+        for node in &self.nodes {
+            if node.is_precompile() ||
+                node.kind().is_any_create() ||
+                node.trace.data.len() < SELECTOR_LEN
+            {
+                continue
+            }
+            let (selector, calldata) = node.trace.data.split_at(SELECTOR_LEN);
+            let key = format!("0x{}-{}", hex::encode(selector), calldata.len());
+            *histogram.entry(key).or_default() += 1;
+        }
+        FourByteFrame(histogram)
+    }
 }
@@ -1,10 +1,15 @@
-use reth_rpc_types::trace::geth::GethDefaultTracingOptions;
+use reth_primitives::U256;
+use reth_rpc_types::trace::{geth::GethDefaultTracingOptions, parity::TraceType};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt};
 
 /// Gives guidance to the [TracingInspector](crate::tracing::TracingInspector).
 ///
 /// Use [TracingInspectorConfig::default_parity] or [TracingInspectorConfig::default_geth] to get
 /// the default configs for specific styles of traces.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub struct TracingInspectorConfig {
     /// Whether to record every individual opcode level step.
     pub record_steps: bool,
@@ -13,9 +18,48 @@ pub struct TracingInspectorConfig {
     /// Whether to record individual stack snapshots.
     pub record_stack_snapshots: bool,
     /// Whether to record state diffs.
+    ///
+    /// This also records the state of every account touched by a call before the transaction, see
+    /// [TracingInspector::prestate](crate::tracing::TracingInspector::prestate).
     pub record_state_diff: bool,
-    /// Whether to ignore precompile calls.
-    pub exclude_precompile_calls: bool,
+    /// How calls to precompiles are recorded.
+    pub precompile_handling: PrecompileHandling,
+    /// Whether to record the preimages of all `KECCAK256` opcodes.
+    ///
+    /// Every distinct hash computed during execution is kept in memory together with its input
+    /// until the inspector is dropped, so this can be expensive for hash-heavy transactions.
+    pub record_keccak_preimages: bool,
+    /// Whether to decode the standard `Error(string)` and `Panic(uint256)` revert reasons of
+    /// reverted calls.
+    ///
+    /// The raw output of the call is always recorded as well.
+    pub decode_revert_reasons: bool,
+    /// Whether to record the [ExecutionStatus](crate::tracing::ExecutionStatus) of the top-level
+    /// call, see [TracingInspector::execution_status](crate::tracing::TracingInspector::execution_status).
+    pub record_execution_result: bool,
+    /// Whether to record the return data of the most recent call, which is reported as the
+    /// `returnData` of geth's struct logs.
+    ///
+    /// Disabling this saves memory for step-only traces that don't report the return data.
+    pub record_returndata: bool,
+    /// Whether to record the logs emitted by the `LOG*` opcodes.
+    pub record_logs: bool,
+    /// The maximum number of opcode level steps to record, unlimited if `None`.
+    ///
+    /// Once the limit is reached, execution continues but no further steps are recorded, see
+    /// [TracingInspector::steps_truncated](crate::tracing::TracingInspector::steps_truncated).
+    pub max_steps: Option<u64>,
+    /// Filters the opcodes for which steps are recorded, all steps are recorded if `None`.
+    pub record_opcode_filter: Option<OpcodeFilter>,
+    /// Whether to record the function selector and calldata size of every call, see
+    /// [TracingInspector::call_selectors](crate::tracing::TracingInspector::call_selectors).
+    pub record_call_selectors: bool,
+    /// The maximum depth of the calls to record, unlimited if `None`.
+    ///
+    /// The top-level call has depth `0`, so a limit of `1` only records the top-level call.
+    /// Calls beyond the limit are still executed, but neither they nor their steps and logs are
+    /// recorded.
+    pub max_call_depth: Option<usize>,
 }
 
 impl TracingInspectorConfig {
@@ -26,7 +70,16 @@ impl TracingInspectorConfig {
             record_memory_snapshots: true,
             record_stack_snapshots: true,
             record_state_diff: false,
-            exclude_precompile_calls: false,
+            precompile_handling: PrecompileHandling::Include,
+            record_keccak_preimages: false,
+            decode_revert_reasons: true,
+            record_execution_result: true,
+            record_returndata: true,
+            record_logs: true,
+            max_steps: None,
+            record_opcode_filter: None,
+            record_call_selectors: false,
+            max_call_depth: None,
         }
     }
 
@@ -39,10 +92,41 @@ impl TracingInspectorConfig {
             record_memory_snapshots: false,
             record_stack_snapshots: false,
             record_state_diff: false,
-            exclude_precompile_calls: true,
+            precompile_handling: PrecompileHandling::ExcludeNoValue,
+            record_keccak_preimages: false,
+            decode_revert_reasons: false,
+            record_execution_result: true,
+            record_returndata: false,
+            record_logs: true,
+            max_steps: None,
+            record_opcode_filter: None,
+            record_call_selectors: false,
+            max_call_depth: None,
         }
     }
 
+    /// Returns a config for parity style traces that also records state diffs.
+    ///
+    /// This is suited for `trace_replayTransaction` with the `stateDiff` trace type.
+    pub const fn default_parity_with_state_diff() -> Self {
+        Self { record_state_diff: true, ..Self::default_parity() }
+    }
+
+    /// Returns a config for parity style traces based on the requested [TraceType]s.
+    ///
+    /// [TraceType::VmTrace] enables opcode level steps and memory snapshots,
+    /// [TraceType::StateDiff] enables state diffs. Otherwise this is the
+    /// [default_parity](Self::default_parity) config.
+    pub fn from_parity_config(trace_types: &HashSet<TraceType>) -> Self {
+        let config = if trace_types.contains(&TraceType::StateDiff) {
+            Self::default_parity_with_state_diff()
+        } else {
+            Self::default_parity()
+        };
+        let vm_trace = trace_types.contains(&TraceType::VmTrace);
+        config.set_steps(vm_trace).set_memory_snapshots(vm_trace)
+    }
+
     /// Returns a config for geth style traces.
     ///
     /// This config does _not_ record opcode level traces and is suited for `debug_traceTransaction`
@@ -52,7 +136,91 @@ impl TracingInspectorConfig {
             record_memory_snapshots: true,
             record_stack_snapshots: true,
             record_state_diff: true,
-            exclude_precompile_calls: false,
+            precompile_handling: PrecompileHandling::Include,
+            record_keccak_preimages: false,
+            decode_revert_reasons: true,
+            record_execution_result: true,
+            record_returndata: true,
+            record_logs: true,
+            max_steps: None,
+            record_opcode_filter: None,
+            record_call_selectors: false,
+            max_call_depth: None,
+        }
+    }
+
+    /// Returns a config for geth's `4byteTracer`.
+    ///
+    /// This config only records the call frames and the function selector of every call, which
+    /// is all that is needed to build the histogram of function selectors and calldata sizes with
+    /// [GethTraceBuilder::geth_four_byte_traces](crate::tracing::GethTraceBuilder::geth_four_byte_traces).
+    pub const fn four_byte_tracer() -> Self {
+        Self {
+            record_steps: false,
+            record_memory_snapshots: false,
+            record_stack_snapshots: false,
+            record_state_diff: false,
+            precompile_handling: PrecompileHandling::ExcludeNoValue,
+            record_keccak_preimages: false,
+            decode_revert_reasons: false,
+            record_execution_result: true,
+            record_returndata: false,
+            record_logs: true,
+            max_steps: None,
+            record_opcode_filter: None,
+            record_call_selectors: true,
+            max_call_depth: None,
+        }
+    }
+
+    /// Alias for [TracingInspectorConfig::four_byte_tracer].
+    pub const fn four_byte() -> Self {
+        Self::four_byte_tracer()
+    }
+
+    /// Returns a config for geth's `prestateTracer`.
+    ///
+    /// This config records the call frames and the state of the accounts they touch before the
+    /// transaction, see [TracingInspector::prestate](crate::tracing::TracingInspector::prestate),
+    /// but no opcode level steps.
+    pub const fn prestate() -> Self {
+        Self {
+            record_steps: false,
+            record_memory_snapshots: false,
+            record_stack_snapshots: false,
+            record_state_diff: true,
+            precompile_handling: PrecompileHandling::Include,
+            record_keccak_preimages: false,
+            decode_revert_reasons: false,
+            record_execution_result: true,
+            record_returndata: false,
+            record_logs: false,
+            max_steps: None,
+            record_opcode_filter: None,
+            record_call_selectors: false,
+            max_call_depth: None,
+        }
+    }
+
+    /// Returns a config that only records the call frames and the logs they emit.
+    ///
+    /// This is suited for replaying logs, e.g. for `eth_getLogs` style queries.
+    pub const fn logs_only() -> Self {
+        Self {
+            record_steps: false,
+            record_memory_snapshots: false,
+            record_stack_snapshots: false,
+            record_state_diff: false,
+            precompile_handling: PrecompileHandling::ExcludeNoValue,
+            record_keccak_preimages: false,
+            decode_revert_reasons: false,
+            record_execution_result: true,
+            record_returndata: false,
+            record_logs: true,
+            max_steps: None,
+            record_opcode_filter: None,
+            record_call_selectors: false,
+            max_call_depth: None,
         }
     }
 
@@ -62,15 +230,50 @@ impl TracingInspectorConfig {
             record_memory_snapshots: config.enable_memory.unwrap_or_default(),
             record_stack_snapshots: !config.disable_stack.unwrap_or_default(),
             record_state_diff: !config.disable_storage.unwrap_or_default(),
+            record_returndata: config.is_return_data_enabled(),
             ..Self::default_geth()
         }
     }
 
+    /// Checks that the config is coherent.
+    ///
+    /// Memory and stack snapshots are taken per step, so they require
+    /// [record_steps](Self::record_steps), otherwise the recorded traces would silently lack
+    /// them. See also [TracingInspectorConfig::enable_required_steps].
+    pub fn validate(&self) -> Result<(), TracingConfigError> {
+        if !self.record_steps {
+            if self.record_memory_snapshots {
+                return Err(TracingConfigError::MemorySnapshotsWithoutSteps)
+            }
+            if self.record_stack_snapshots {
+                return Err(TracingConfigError::StackSnapshotsWithoutSteps)
+            }
+        }
+        Ok(())
+    }
+
+    /// Enables step recording if memory or stack snapshots are enabled, which are taken per
+    /// step.
+    pub fn enable_required_steps(mut self) -> Self {
+        self.record_steps |= self.record_memory_snapshots || self.record_stack_snapshots;
+        self
+    }
+
     /// Configure whether calls to precompiles should be ignored.
     ///
-    /// If set to `true`, calls to precompiles without value transfers will be ignored.
-    pub fn set_exclude_precompile_calls(mut self, exclude_precompile_calls: bool) -> Self {
-        self.exclude_precompile_calls = exclude_precompile_calls;
+    /// If set to `true`, calls to precompiles without value transfers will be ignored, see
+    /// [PrecompileHandling::ExcludeNoValue].
+    pub fn set_exclude_precompile_calls(self, exclude_precompile_calls: bool) -> Self {
+        self.set_precompile_handling(if exclude_precompile_calls {
+            PrecompileHandling::ExcludeNoValue
+        } else {
+            PrecompileHandling::Include
+        })
+    }
+
+    /// Configure how calls to precompiles should be recorded
+    pub fn set_precompile_handling(mut self, precompile_handling: PrecompileHandling) -> Self {
+        self.precompile_handling = precompile_handling;
         self
     }
 
@@ -97,4 +300,351 @@ impl TracingInspectorConfig {
         self.record_state_diff = record_state_diff;
         self
     }
+
+    /// Configure whether the tracer should record the preimages of `KECCAK256` opcodes
+    pub fn set_record_keccak_preimages(mut self, record_keccak_preimages: bool) -> Self {
+        self.record_keccak_preimages = record_keccak_preimages;
+        self
+    }
+
+    /// Configure whether the tracer should decode the revert reasons of reverted calls
+    pub fn set_decode_revert_reasons(mut self, decode_revert_reasons: bool) -> Self {
+        self.decode_revert_reasons = decode_revert_reasons;
+        self
+    }
+
+    /// Configure whether the tracer should record the execution status of the top-level call
+    pub fn set_record_execution_result(mut self, record_execution_result: bool) -> Self {
+        self.record_execution_result = record_execution_result;
+        self
+    }
+
+    /// Configure the maximum number of opcode level steps to record
+    pub fn set_max_steps(mut self, max_steps: Option<u64>) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Configure the maximum depth of the calls to record
+    pub fn set_max_call_depth(mut self, max_call_depth: Option<usize>) -> Self {
+        self.max_call_depth = max_call_depth;
+        self
+    }
+
+    /// Returns `true` if calls at the given depth should be recorded.
+    #[inline]
+    pub fn is_call_depth_recorded(&self, depth: usize) -> bool {
+        self.max_call_depth.map_or(true, |max_call_depth| depth < max_call_depth)
+    }
+
+    /// Configure the tracer to only record steps of the given opcodes
+    pub fn only_opcodes(mut self, opcodes: &[u8]) -> Self {
+        self.record_opcode_filter = Some(OpcodeFilter::allow(opcodes));
+        self
+    }
+
+    /// Configure the tracer to not record steps of the given opcodes
+    pub fn exclude_opcodes(mut self, opcodes: &[u8]) -> Self {
+        self.record_opcode_filter = Some(OpcodeFilter::deny(opcodes));
+        self
+    }
+
+    /// Returns `true` if steps of the given opcode should be recorded.
+    #[inline]
+    pub fn is_opcode_recorded(&self, opcode: u8) -> bool {
+        self.record_opcode_filter.map_or(true, |filter| filter.is_recorded(opcode))
+    }
+
+    /// Configure whether the tracer should record the return data of calls
+    pub fn set_record_returndata(mut self, record_returndata: bool) -> Self {
+        self.record_returndata = record_returndata;
+        self
+    }
+
+    /// Configure whether the tracer should record the logs emitted by the `LOG*` opcodes.
+    pub fn set_record_logs(mut self, record_logs: bool) -> Self {
+        self.record_logs = record_logs;
+        self
+    }
+
+    /// Configure whether the tracer should record the function selectors of calls
+    pub fn set_record_call_selectors(mut self, record_call_selectors: bool) -> Self {
+        self.record_call_selectors = record_call_selectors;
+        self
+    }
+}
+
+/// An incoherent combination of settings in a [TracingInspectorConfig].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TracingConfigError {
+    /// Memory snapshots are enabled, but steps are not recorded.
+    MemorySnapshotsWithoutSteps,
+    /// Stack snapshots are enabled, but steps are not recorded.
+    StackSnapshotsWithoutSteps,
+}
+
+impl fmt::Display for TracingConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TracingConfigError::MemorySnapshotsWithoutSteps => {
+                f.write_str("memory snapshots require recording steps")
+            }
+            TracingConfigError::StackSnapshotsWithoutSteps => {
+                f.write_str("stack snapshots require recording steps")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TracingConfigError {}
+
+/// Determines which calls to precompiles are recorded.
+///
+/// This only applies to nested calls, the top-level call is always recorded.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub enum PrecompileHandling {
+    /// Record all calls to precompiles.
+    #[default]
+    Include,
+    /// Ignore calls to precompiles that don't transfer value.
+    ///
+    /// Calls that transfer value are still recorded, because the value transfer is part of the
+    /// trace.
+    ExcludeNoValue,
+    /// Ignore all calls to precompiles, including calls that transfer value.
+    ExcludeAll,
+}
+
+impl PrecompileHandling {
+    /// Returns `true` if a nested call to a precompile that transfers the given value should be
+    /// ignored.
+    pub fn excludes_call(&self, value: U256) -> bool {
+        match self {
+            PrecompileHandling::Include => false,
+            PrecompileHandling::ExcludeNoValue => value == U256::ZERO,
+            PrecompileHandling::ExcludeAll => true,
+        }
+    }
+}
+
+/// An allow-list or deny-list of opcodes for which steps are recorded.
+///
+/// See [TracingInspectorConfig::only_opcodes] and [TracingInspectorConfig::exclude_opcodes].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct OpcodeFilter {
+    /// Bitmap of the listed opcodes.
+    opcodes: [u64; 4],
+    /// Whether the listed opcodes are allowed or denied.
+    allow: bool,
+}
+
+impl OpcodeFilter {
+    /// Returns a filter that only records steps of the given opcodes.
+    pub fn allow(opcodes: &[u8]) -> Self {
+        Self { opcodes: Self::bitmap(opcodes), allow: true }
+    }
+
+    /// Returns a filter that records steps of all opcodes except the given ones.
+    pub fn deny(opcodes: &[u8]) -> Self {
+        Self { opcodes: Self::bitmap(opcodes), allow: false }
+    }
+
+    /// Returns `true` if steps of the given opcode should be recorded.
+    #[inline]
+    pub fn is_recorded(&self, opcode: u8) -> bool {
+        let listed = self.opcodes[(opcode / 64) as usize] & (1 << (opcode % 64)) != 0;
+        listed == self.allow
+    }
+
+    fn bitmap(opcodes: &[u8]) -> [u64; 4] {
+        let mut bitmap = [0u64; 4];
+        for opcode in opcodes {
+            bitmap[(opcode / 64) as usize] |= 1 << (opcode % 64);
+        }
+        bitmap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keccak_preimages_disabled_by_default() {
+        assert!(!TracingInspectorConfig::all().record_keccak_preimages);
+        assert!(!TracingInspectorConfig::default_parity().record_keccak_preimages);
+        assert!(!TracingInspectorConfig::default_geth().record_keccak_preimages);
+
+        let config = TracingInspectorConfig::default_geth().set_record_keccak_preimages(true);
+        assert!(config.record_keccak_preimages);
+        assert_eq!(
+            config.set_record_keccak_preimages(false),
+            TracingInspectorConfig::default_geth()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_roundtrip() {
+        let config = TracingInspectorConfig::default_geth();
+        let json = serde_json::to_value(config).unwrap();
+        assert_eq!(json["recordSteps"], true);
+        assert_eq!(json["precompileHandling"], "include");
+        assert_eq!(serde_json::from_value::<TracingInspectorConfig>(json).unwrap(), config);
+
+        let config = TracingInspectorConfig::default_parity()
+            .only_opcodes(&[0x54, 0x55])
+            .set_max_steps(Some(10));
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(serde_json::from_str::<TracingInspectorConfig>(&json).unwrap(), config);
+    }
+
+    #[test]
+    fn four_byte_preset() {
+        assert_eq!(
+            TracingInspectorConfig::four_byte_tracer(),
+            TracingInspectorConfig {
+                record_steps: false,
+                record_memory_snapshots: false,
+                record_stack_snapshots: false,
+                record_state_diff: false,
+                precompile_handling: PrecompileHandling::ExcludeNoValue,
+                record_keccak_preimages: false,
+                decode_revert_reasons: false,
+                record_execution_result: true,
+                record_returndata: false,
+                record_logs: true,
+                max_steps: None,
+                record_opcode_filter: None,
+                record_call_selectors: true,
+                max_call_depth: None,
+            }
+        );
+        assert_eq!(TracingInspectorConfig::four_byte(), TracingInspectorConfig::four_byte_tracer());
+    }
+
+    #[test]
+    fn prestate_preset() {
+        let config = TracingInspectorConfig::prestate();
+        assert!(config.record_state_diff);
+        assert!(!config.record_steps);
+        assert!(!config.record_memory_snapshots);
+        assert!(!config.record_stack_snapshots);
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_snapshots_require_steps() {
+        assert_eq!(TracingInspectorConfig::all().validate(), Ok(()));
+        assert_eq!(TracingInspectorConfig::default_parity().validate(), Ok(()));
+        assert_eq!(TracingInspectorConfig::default_geth().validate(), Ok(()));
+
+        let config = TracingInspectorConfig::default_parity().set_memory_snapshots(true);
+        assert_eq!(config.validate(), Err(TracingConfigError::MemorySnapshotsWithoutSteps));
+        let config = TracingInspectorConfig::default_parity().set_stack_snapshots(true);
+        assert_eq!(config.validate(), Err(TracingConfigError::StackSnapshotsWithoutSteps));
+
+        let config = config.enable_required_steps();
+        assert!(config.record_steps);
+        assert_eq!(config.validate(), Ok(()));
+
+        let config = TracingInspectorConfig::default_parity().enable_required_steps();
+        assert_eq!(config, TracingInspectorConfig::default_parity());
+    }
+
+    #[test]
+    fn precompile_handling() {
+        let value = U256::from(1);
+        assert!(!PrecompileHandling::Include.excludes_call(U256::ZERO));
+        assert!(!PrecompileHandling::Include.excludes_call(value));
+        assert!(PrecompileHandling::ExcludeNoValue.excludes_call(U256::ZERO));
+        assert!(!PrecompileHandling::ExcludeNoValue.excludes_call(value));
+        assert!(PrecompileHandling::ExcludeAll.excludes_call(U256::ZERO));
+        assert!(PrecompileHandling::ExcludeAll.excludes_call(value));
+
+        let config = TracingInspectorConfig::default_geth();
+        assert_eq!(config.precompile_handling, PrecompileHandling::Include);
+        let config = config.set_exclude_precompile_calls(true);
+        assert_eq!(config.precompile_handling, PrecompileHandling::ExcludeNoValue);
+        let config = config.set_exclude_precompile_calls(false);
+        assert_eq!(config.precompile_handling, PrecompileHandling::Include);
+    }
+
+    #[test]
+    fn opcode_filter() {
+        let config = TracingInspectorConfig::default_geth();
+        assert!((0..=u8::MAX).all(|op| config.is_opcode_recorded(op)));
+
+        let config = config.only_opcodes(&[0x54, 0x55]);
+        assert!(config.is_opcode_recorded(0x54));
+        assert!(config.is_opcode_recorded(0x55));
+        assert!(!config.is_opcode_recorded(0x01));
+        assert!(!config.is_opcode_recorded(0xff));
+
+        let config = config.exclude_opcodes(&[0x01, 0xff]);
+        assert!(!config.is_opcode_recorded(0x01));
+        assert!(!config.is_opcode_recorded(0xff));
+        assert!(config.is_opcode_recorded(0x54));
+    }
+
+    #[test]
+    fn returndata_defaults() {
+        assert!(TracingInspectorConfig::all().record_returndata);
+        assert!(TracingInspectorConfig::default_geth().record_returndata);
+        assert!(!TracingInspectorConfig::default_parity().record_returndata);
+        assert!(!TracingInspectorConfig::four_byte_tracer().record_returndata);
+
+        let opts = GethDefaultTracingOptions::default();
+        assert!(!TracingInspectorConfig::from_geth_config(&opts).record_returndata);
+        let opts =
+            GethDefaultTracingOptions { enable_return_data: Some(true), ..Default::default() };
+        assert!(TracingInspectorConfig::from_geth_config(&opts).record_returndata);
+
+        let config = TracingInspectorConfig::default_parity().set_record_returndata(true);
+        assert!(config.record_returndata);
+    }
+
+    #[test]
+    fn parity_state_diff_config() {
+        let config = TracingInspectorConfig::default_parity_with_state_diff();
+        assert!(config.record_state_diff);
+        assert_eq!(config.precompile_handling, PrecompileHandling::ExcludeNoValue);
+        assert!(!config.record_steps);
+    }
+
+    #[test]
+    fn from_parity_config() {
+        let parity = TracingInspectorConfig::default_parity();
+        let vm_trace = parity.set_steps(true).set_memory_snapshots(true);
+        let state_diff = TracingInspectorConfig::default_parity_with_state_diff();
+        let all = state_diff.set_steps(true).set_memory_snapshots(true);
+
+        let cases = [
+            (vec![], parity),
+            (vec![TraceType::Trace], parity),
+            (vec![TraceType::VmTrace], vm_trace),
+            (vec![TraceType::StateDiff], state_diff),
+            (vec![TraceType::Trace, TraceType::VmTrace], vm_trace),
+            (vec![TraceType::Trace, TraceType::StateDiff], state_diff),
+            (vec![TraceType::VmTrace, TraceType::StateDiff], all),
+            (vec![TraceType::Trace, TraceType::VmTrace, TraceType::StateDiff], all),
+        ];
+        for (trace_types, expected) in cases {
+            let trace_types = trace_types.into_iter().collect::<HashSet<_>>();
+            assert_eq!(TracingInspectorConfig::from_parity_config(&trace_types), expected);
+        }
+    }
+
+    #[test]
+    fn execution_result_recorded_by_default() {
+        assert!(TracingInspectorConfig::all().record_execution_result);
+        assert!(TracingInspectorConfig::default_parity().record_execution_result);
+        assert!(TracingInspectorConfig::default_geth().record_execution_result);
+        assert!(TracingInspectorConfig::four_byte_tracer().record_execution_result);
+
+        let config = TracingInspectorConfig::default_parity().set_record_execution_result(false);
+        assert!(!config.record_execution_result);
+    }
 }
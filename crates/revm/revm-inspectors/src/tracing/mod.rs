@@ -1,17 +1,19 @@
 use crate::tracing::{
     types::{CallKind, LogCallOrder, RawLog},
-    utils::get_create_address,
+    utils::{decode_revert_reason, get_create_address},
 };
 pub use arena::CallTraceArena;
-use reth_primitives::{bytes::Bytes, Address, H256, U256};
+use reth_primitives::{bytes::Bytes, keccak256, Address, Selector, H256, U256};
 use revm::{
     inspectors::GasInspector,
     interpreter::{
         opcode, return_ok, CallInputs, CallScheme, CreateInputs, Gas, InstructionResult,
         Interpreter, OpCode,
     },
+    primitives::AccountInfo,
     Database, EVMData, Inspector, JournalEntry,
 };
+use std::collections::HashMap;
 use types::{CallTrace, CallTraceStep};
 
 mod arena;
@@ -26,9 +28,10 @@ use crate::tracing::{
     types::{CallTraceNode, StorageChange},
 };
 pub use builder::{geth::GethTraceBuilder, parity::ParityTraceBuilder};
-pub use config::TracingInspectorConfig;
+pub use config::{OpcodeFilter, PrecompileHandling, TracingConfigError, TracingInspectorConfig};
 pub use fourbyte::FourByteInspector;
 pub use opcount::OpcodeCountInspector;
+pub use types::ExecutionStatus;
 
 #[cfg(feature = "js-tracer")]
 pub mod js;
@@ -49,12 +52,27 @@ pub struct TracingInspector {
     traces: CallTraceArena,
     /// Tracks active calls
     trace_stack: Vec<usize>,
-    /// Tracks active steps
-    step_stack: Vec<StackStep>,
+    /// Tracks active steps, `None` for steps that are not recorded
+    step_stack: Vec<Option<StackStep>>,
     /// Tracks the return value of the last call
     last_call_return_data: Option<Bytes>,
     /// The gas inspector used to track remaining gas.
     gas_inspector: GasInspector,
+    /// Recorded `KECCAK256` preimages, keyed by the resulting hash.
+    keccak_preimages: HashMap<H256, Bytes>,
+    /// The status of the top-level call, once it finished.
+    execution_status: Option<ExecutionStatus>,
+    /// The number of recorded steps.
+    recorded_steps: u64,
+    /// Whether steps were dropped because [TracingInspectorConfig::max_steps] was reached.
+    steps_truncated: bool,
+    /// The function selectors and calldata sizes of all calls, in call order.
+    call_selectors: Vec<(Selector, usize)>,
+    /// The number of active calls that are not recorded because they exceed
+    /// [TracingInspectorConfig::max_call_depth].
+    ignored_calls: usize,
+    /// The state of the touched accounts before the transaction.
+    prestate: HashMap<Address, AccountInfo>,
 }
 
 // === impl TracingInspector ===
@@ -69,9 +87,53 @@ impl TracingInspector {
             step_stack: vec![],
             last_call_return_data: None,
             gas_inspector: Default::default(),
+            keccak_preimages: Default::default(),
+            execution_status: None,
+            recorded_steps: 0,
+            steps_truncated: false,
+            call_selectors: vec![],
+            ignored_calls: 0,
+            prestate: Default::default(),
         }
     }
 
+    /// Returns all recorded `KECCAK256` preimages, keyed by the resulting hash.
+    ///
+    /// This is only populated if [TracingInspectorConfig::record_keccak_preimages] is enabled.
+    pub fn keccak_preimages(&self) -> &HashMap<H256, Bytes> {
+        &self.keccak_preimages
+    }
+
+    /// Returns the [ExecutionStatus] of the top-level call, once it finished.
+    ///
+    /// This is only recorded if [TracingInspectorConfig::record_execution_result] is enabled.
+    pub fn execution_status(&self) -> Option<ExecutionStatus> {
+        self.execution_status
+    }
+
+    /// Returns `true` if steps were not recorded because [TracingInspectorConfig::max_steps] was
+    /// reached.
+    pub fn steps_truncated(&self) -> bool {
+        self.steps_truncated
+    }
+
+    /// Returns the function selector and the size of the calldata following it of every call
+    /// with at least 4 bytes of input, in call order.
+    ///
+    /// This is only recorded if [TracingInspectorConfig::record_call_selectors] is enabled.
+    pub fn call_selectors(&self) -> &[(Selector, usize)] {
+        &self.call_selectors
+    }
+
+    /// Returns the state before the transaction of every account that was the sender or the target
+    /// of a recorded call or create, as required by geth's `prestateTracer`.
+    ///
+    /// Accounts that did not exist are recorded with the default, empty state. This is only
+    /// recorded if [TracingInspectorConfig::record_state_diff] is enabled.
+    pub fn prestate(&self) -> &HashMap<Address, AccountInfo> {
+        &self.prestate
+    }
+
     /// Consumes the Inspector and returns a [ParityTraceBuilder].
     pub fn into_parity_builder(self) -> ParityTraceBuilder {
         ParityTraceBuilder::new(self.traces.arena, self.config)
@@ -172,14 +234,22 @@ impl TracingInspector {
         created_address: Option<Address>,
     ) {
         let trace_idx = self.pop_trace_idx();
+        if self.config.record_execution_result && self.trace_stack.is_empty() {
+            self.execution_status = Some(ExecutionStatus::from_instruction_result(status));
+        }
         let trace = &mut self.traces.arena[trace_idx].trace;
 
         trace.gas_used = gas.spend();
         trace.gas_limit = gas.limit();
         trace.status = status;
         trace.success = matches!(status, return_ok!());
-        trace.output = output.clone();
-        self.last_call_return_data = Some(output);
+        if self.config.decode_revert_reasons && status == InstructionResult::Revert {
+            trace.revert_reason = decode_revert_reason(&output);
+        }
+        if self.config.record_returndata {
+            self.last_call_return_data = Some(output.clone());
+        }
+        trace.output = output;
 
         if let Some(address) = created_address {
             // A new contract was created via CREATE
@@ -187,6 +257,20 @@ impl TracingInspector {
         }
     }
 
+    /// Records the state of the account before the transaction, unless it was already recorded.
+    ///
+    /// Invoked on [Inspector::call] and [Inspector::create].
+    fn record_prestate<DB: Database>(&mut self, data: &mut EVMData<'_, DB>, address: Address) {
+        if self.prestate.contains_key(&address) {
+            return
+        }
+        // the database is not modified before the transaction is committed, so it still holds
+        // the state before the transaction
+        if let Ok(info) = data.db.basic(address) {
+            self.prestate.insert(address, info.unwrap_or_default());
+        }
+    }
+
     /// Starts tracking a step
     ///
     /// Invoked on [Inspector::step]
@@ -196,12 +280,23 @@ impl TracingInspector {
     /// This expects an existing [CallTrace], in other words, this panics if not within the context
     /// of a call.
     fn start_step<DB: Database>(&mut self, interp: &mut Interpreter, data: &mut EVMData<'_, DB>) {
+        let pc = interp.program_counter();
+        let op = interp.contract.bytecode.bytecode()[pc];
+        if self.ignored_calls > 0 || !self.config.is_opcode_recorded(op) {
+            self.step_stack.push(None);
+            return
+        }
+        if self.config.max_steps.map_or(false, |max_steps| self.recorded_steps >= max_steps) {
+            self.steps_truncated = true;
+            self.step_stack.push(None);
+            return
+        }
+        self.recorded_steps += 1;
+
         let trace_idx = self.last_trace_idx();
         let trace = &mut self.traces.arena[trace_idx];
 
-        self.step_stack.push(StackStep { trace_idx, step_idx: trace.trace.steps.len() });
-
-        let pc = interp.program_counter();
+        self.step_stack.push(Some(StackStep { trace_idx, step_idx: trace.trace.steps.len() }));
 
         let memory =
             self.config.record_memory_snapshots.then(|| interp.memory.clone()).unwrap_or_default();
@@ -211,8 +306,7 @@ impl TracingInspector {
         trace.trace.steps.push(CallTraceStep {
             depth: data.journaled_state.depth(),
             pc,
-            op: OpCode::try_from_u8(interp.contract.bytecode.bytecode()[pc])
-                .expect("is valid opcode;"),
+            op: OpCode::try_from_u8(op).expect("is valid opcode;"),
             contract: interp.contract.address,
             stack,
             memory,
@@ -227,6 +321,33 @@ impl TracingInspector {
         });
     }
 
+    /// Records the input of the `KECCAK256` opcode that is about to be executed.
+    ///
+    /// Invoked on [Inspector::step]
+    ///
+    /// Only inputs that are already fully allocated in memory are recorded, this avoids allocating
+    /// for inputs that would expand memory beyond what the transaction can pay for.
+    fn record_keccak_preimage(&mut self, interp: &Interpreter) {
+        // KECCAK256
+        if interp.contract.bytecode.bytecode()[interp.program_counter()] != 0x20 {
+            return
+        }
+
+        let mut stack = interp.stack.data().iter().rev();
+        let (Some(offset), Some(len)) = (stack.next(), stack.next()) else { return };
+        let (Ok(offset), Ok(len)) = (usize::try_from(*offset), usize::try_from(*len)) else {
+            return
+        };
+
+        let memory = interp.memory.data();
+        let Some(input) = offset.checked_add(len).and_then(|end| memory.get(offset..end)) else {
+            return
+        };
+
+        let preimage = Bytes::copy_from_slice(input);
+        self.keccak_preimages.insert(keccak256(&preimage), preimage);
+    }
+
     /// Fills the current trace with the output of a step.
     ///
     /// Invoked on [Inspector::step_end].
@@ -236,8 +357,12 @@ impl TracingInspector {
         data: &mut EVMData<'_, DB>,
         status: InstructionResult,
     ) {
-        let StackStep { trace_idx, step_idx } =
-            self.step_stack.pop().expect("can't fill step without starting a step first");
+        let Some(StackStep { trace_idx, step_idx }) =
+            self.step_stack.pop().expect("can't fill step without starting a step first")
+        else {
+            // the step was not recorded
+            return
+        };
         let step = &mut self.traces.arena[trace_idx].trace.steps[step_idx];
 
         if self.config.record_memory_snapshots {
@@ -304,6 +429,10 @@ where
         data: &mut EVMData<'_, DB>,
         is_static: bool,
     ) -> InstructionResult {
+        if self.config.record_keccak_preimages {
+            self.record_keccak_preimage(interp);
+        }
+
         if self.config.record_steps {
             self.gas_inspector.step(interp, data, is_static);
             self.start_step(interp, data);
@@ -321,6 +450,10 @@ where
     ) {
         self.gas_inspector.log(evm_data, address, topics, data);
 
+        if !self.config.record_logs || self.ignored_calls > 0 {
+            return
+        }
+
         let trace_idx = self.last_trace_idx();
         let trace = &mut self.traces.arena[trace_idx];
         trace.ordering.push(LogCallOrder::Log(trace.logs.len()));
@@ -350,6 +483,12 @@ where
     ) -> (InstructionResult, Gas, Bytes) {
         self.gas_inspector.call(data, inputs, is_static);
 
+        let depth = data.journaled_state.depth() as usize;
+        if self.ignored_calls > 0 || !self.config.is_call_depth_recorded(depth) {
+            self.ignored_calls += 1;
+            return (InstructionResult::Continue, Gas::new(0), Bytes::new())
+        }
+
         // determine correct `from` and `to` based on the call scheme
         let (from, to) = match inputs.context.scheme {
             CallScheme::DelegateCall | CallScheme::CallCode => {
@@ -369,12 +508,28 @@ where
             inputs.transfer.value
         };
 
+        // like geth's 4byte tracer, calls to precompiles are not recorded
+        if self.config.record_call_selectors &&
+            inputs.input.len() >= 4 &&
+            !data.precompiles.contains(&to)
+        {
+            let (selector, calldata) = inputs.input.split_at(4);
+            let selector = Selector::try_from(selector).expect("selector is 4 bytes");
+            self.call_selectors.push((selector, calldata.len()));
+        }
+
+        if self.config.record_state_diff {
+            self.record_prestate(data, from);
+            self.record_prestate(data, to);
+        }
+
         // if calls to precompiles should be excluded, check whether this is a call to a precompile
-        let maybe_precompile =
-            self.config.exclude_precompile_calls.then(|| is_precompile_call(data, &to, value));
+        let handling = self.config.precompile_handling;
+        let maybe_precompile = (handling != PrecompileHandling::Include)
+            .then(|| is_precompile_call(data, &to, value, handling));
 
         self.start_trace_on_call(
-            data.journaled_state.depth() as usize,
+            depth,
             to,
             inputs.input.clone(),
             value,
@@ -397,6 +552,11 @@ where
     ) -> (InstructionResult, Gas, Bytes) {
         self.gas_inspector.call_end(data, inputs, gas, ret, out.clone(), is_static);
 
+        if self.ignored_calls > 0 {
+            self.ignored_calls -= 1;
+            return (ret, gas, out)
+        }
+
         self.fill_trace_on_call_end(data, ret, &gas, out.clone(), None);
 
         (ret, gas, out)
@@ -409,11 +569,22 @@ where
     ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
         self.gas_inspector.create(data, inputs);
 
+        let depth = data.journaled_state.depth() as usize;
+        if self.ignored_calls > 0 || !self.config.is_call_depth_recorded(depth) {
+            self.ignored_calls += 1;
+            return (InstructionResult::Continue, None, Gas::new(inputs.gas_limit), Bytes::default())
+        }
+
         let _ = data.journaled_state.load_account(inputs.caller, data.db);
         let nonce = data.journaled_state.account(inputs.caller).info.nonce;
+        let address = get_create_address(inputs, nonce);
+        if self.config.record_state_diff {
+            self.record_prestate(data, inputs.caller);
+            self.record_prestate(data, address);
+        }
         self.start_trace_on_call(
-            data.journaled_state.depth() as usize,
-            get_create_address(inputs, nonce),
+            depth,
+            address,
             inputs.init_code.clone(),
             inputs.value,
             inputs.scheme.into(),
@@ -439,6 +610,11 @@ where
     ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
         self.gas_inspector.create_end(data, inputs, status, address, gas, retdata.clone());
 
+        if self.ignored_calls > 0 {
+            self.ignored_calls -= 1;
+            return (status, address, gas, retdata)
+        }
+
         // get the code of the created contract
         let code = address
             .and_then(|address| {
@@ -457,6 +633,9 @@ where
     }
 
     fn selfdestruct(&mut self, _contract: Address, target: Address) {
+        if self.ignored_calls > 0 {
+            return
+        }
         let trace_idx = self.last_trace_idx();
         let trace = &mut self.traces.arena[trace_idx].trace;
         trace.selfdestruct_refund_target = Some(target)
@@ -469,11 +648,204 @@ struct StackStep {
     step_idx: usize,
 }
 
-/// Returns true if this a call to a precompile contract with `depth > 0 && value == 0`.
+/// Returns true if this a call to a precompile contract with `depth > 0` that is excluded by the
+/// given [PrecompileHandling].
 #[inline]
-fn is_precompile_call<DB: Database>(data: &EVMData<'_, DB>, to: &Address, value: U256) -> bool {
+fn is_precompile_call<DB: Database>(
+    data: &EVMData<'_, DB>,
+    to: &Address,
+    value: U256,
+    handling: PrecompileHandling,
+) -> bool {
     if data.precompiles.contains(to) {
-        return data.journaled_state.depth() > 0 && value == U256::ZERO
+        return data.journaled_state.depth() > 0 && handling.excludes_call(value)
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{
+        db::{CacheDB, EmptyDB},
+        primitives::{Bytecode, TransactTo, B160},
+        EVM,
+    };
+    use std::collections::BTreeMap;
+
+    /// Executes the given code with the inspector and returns the number of recorded steps.
+    fn recorded_steps(code: Vec<u8>, inspector: &mut TracingInspector) -> usize {
+        let address = B160::from_low_u64_be(0x1000);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            address,
+            AccountInfo { code: Some(Bytecode::new_raw(code.into())), ..Default::default() },
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.transact_to = TransactTo::Call(address);
+        evm.env.tx.gas_limit = 100_000;
+        evm.inspect(&mut *inspector).unwrap();
+
+        inspector.traces.arena.iter().map(|node| node.trace.steps.len()).sum()
+    }
+
+    #[test]
+    fn records_logs_only() {
+        // PUSH1 0x00, PUSH1 0x00, LOG0, STOP
+        let code = vec![opcode::PUSH1, 0x00, opcode::PUSH1, 0x00, opcode::LOG0, opcode::STOP];
+
+        let mut inspector = TracingInspector::new(TracingInspectorConfig::logs_only());
+        assert_eq!(recorded_steps(code.clone(), &mut inspector), 0);
+        assert_eq!(inspector.traces.arena[0].logs.len(), 1);
+
+        let config = TracingInspectorConfig::logs_only().set_record_logs(false);
+        let mut inspector = TracingInspector::new(config);
+        recorded_steps(code, &mut inspector);
+        assert!(inspector.traces.arena[0].logs.is_empty());
+    }
+
+    /// Returns code that calls `0x2000` with the selector `0xdeadbeef` followed by 32 bytes of
+    /// calldata.
+    fn nested_call_code() -> Vec<u8> {
+        call_code(0x2000)
+    }
+
+    /// Returns code that calls `callee` with the selector `0xdeadbeef` followed by 32 bytes of
+    /// calldata.
+    fn call_code(callee: u16) -> Vec<u8> {
+        let callee = callee.to_be_bytes();
+        // PUSH4 0xdeadbeef, PUSH1 0xe0, SHL, PUSH1 0x00, MSTORE: store the selector at 0
+        let mut code = vec![opcode::PUSH4, 0xde, 0xad, 0xbe, 0xef, opcode::PUSH1, 0xe0];
+        code.extend([opcode::SHL, opcode::PUSH1, 0x00, opcode::MSTORE]);
+        // CALL the callee with the selector and 32 bytes of calldata, then STOP
+        code.extend([opcode::PUSH1, 0x00, opcode::PUSH1, 0x00, opcode::PUSH1, 0x24]);
+        code.extend([opcode::PUSH1, 0x00, opcode::PUSH1, 0x00, opcode::PUSH2, callee[0]]);
+        code.extend([callee[1], opcode::GAS, opcode::CALL, opcode::STOP]);
+        code
+    }
+
+    #[test]
+    fn records_call_selectors() {
+        let code = nested_call_code();
+
+        let mut inspector = TracingInspector::new(TracingInspectorConfig::four_byte_tracer());
+        assert_eq!(recorded_steps(code.clone(), &mut inspector), 0);
+        assert_eq!(inspector.call_selectors(), &[([0xde, 0xad, 0xbe, 0xef], 32)]);
+
+        // calls to the ecrecover precompile are skipped
+        let mut inspector = TracingInspector::new(TracingInspectorConfig::four_byte_tracer());
+        recorded_steps(call_code(0x01), &mut inspector);
+        assert!(inspector.call_selectors().is_empty());
+
+        let mut inspector = TracingInspector::new(TracingInspectorConfig::default_parity());
+        recorded_steps(code, &mut inspector);
+        assert!(inspector.call_selectors().is_empty());
+    }
+
+    #[test]
+    fn four_byte_traces_skip_precompiles() {
+        let mut inspector = TracingInspector::new(TracingInspectorConfig::four_byte_tracer());
+        recorded_steps(nested_call_code(), &mut inspector);
+        let frame = inspector.into_geth_builder().geth_four_byte_traces();
+        assert_eq!(frame.0, BTreeMap::from([("0xdeadbeef-32".to_string(), 1)]));
+
+        // the ecrecover call is still pushed to the arena, but not part of the histogram
+        let mut inspector = TracingInspector::new(TracingInspectorConfig::four_byte_tracer());
+        recorded_steps(call_code(0x01), &mut inspector);
+        assert!(inspector.traces.arena.iter().any(|node| node.is_precompile()));
+        assert!(inspector.into_geth_builder().geth_four_byte_traces().0.is_empty());
+    }
+
+    #[test]
+    fn limits_call_depth() {
+        let code = nested_call_code();
+
+        let mut inspector = TracingInspector::new(TracingInspectorConfig::default_geth());
+        recorded_steps(code.clone(), &mut inspector);
+        assert_eq!(inspector.traces.arena.len(), 2);
+        let top_level_steps = inspector.traces.arena[0].trace.steps.len();
+
+        let config = TracingInspectorConfig::default_geth().set_max_call_depth(Some(1));
+        let mut inspector = TracingInspector::new(config);
+        assert_eq!(recorded_steps(code, &mut inspector), top_level_steps);
+        assert_eq!(inspector.traces.arena.len(), 1);
+        assert!(inspector.traces.arena[0].children.is_empty());
+        // execution continued after the unrecorded call
+        let last_step = inspector.traces.arena[0].trace.steps.last().unwrap();
+        assert_eq!(last_step.op.u8(), opcode::STOP);
+        assert_eq!(inspector.execution_status(), Some(ExecutionStatus::Success));
+    }
+
+    #[test]
+    fn records_prestate_of_value_transfer() {
+        let (sender, recipient) = (B160::from_low_u64_be(0x1000), B160::from_low_u64_be(0x2000));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            sender,
+            AccountInfo { balance: U256::from(100), nonce: 1, ..Default::default() },
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.caller = sender;
+        evm.env.tx.transact_to = TransactTo::Call(recipient);
+        evm.env.tx.value = U256::from(10);
+        evm.env.tx.gas_limit = 21_000;
+        let mut inspector = TracingInspector::new(TracingInspectorConfig::prestate());
+        let result = evm.inspect(&mut inspector).unwrap();
+        assert!(result.result.is_success());
+
+        let prestate = inspector.prestate();
+        assert_eq!(prestate.len(), 2);
+        assert_eq!(prestate[&sender].balance, U256::from(100));
+        assert_eq!(prestate[&sender].nonce, 1);
+        assert_eq!(prestate[&recipient], AccountInfo::default());
+    }
+
+    #[test]
+    fn truncates_steps_at_limit() {
+        // JUMPDEST, PUSH1 0x00, JUMP: loops until out of gas
+        let code = vec![opcode::JUMPDEST, opcode::PUSH1, 0x00, opcode::JUMP];
+
+        let config = TracingInspectorConfig::default_geth().set_max_steps(Some(100));
+        let mut inspector = TracingInspector::new(config);
+        assert_eq!(recorded_steps(code.clone(), &mut inspector), 100);
+        assert!(inspector.steps_truncated());
+
+        let mut inspector = TracingInspector::new(TracingInspectorConfig::default_geth());
+        assert!(recorded_steps(code, &mut inspector) > 100);
+        assert!(!inspector.steps_truncated());
+    }
+
+    #[test]
+    fn records_only_filtered_opcodes() {
+        // PUSH1 0x01, PUSH1 0x02, ADD, PUSH1 0x00, SSTORE, PUSH1 0x00, SLOAD, STOP
+        let code = vec![
+            opcode::PUSH1,
+            0x01,
+            opcode::PUSH1,
+            0x02,
+            opcode::ADD,
+            opcode::PUSH1,
+            0x00,
+            opcode::SSTORE,
+            opcode::PUSH1,
+            0x00,
+            opcode::SLOAD,
+            opcode::STOP,
+        ];
+
+        let config =
+            TracingInspectorConfig::default_geth().only_opcodes(&[opcode::SLOAD, opcode::SSTORE]);
+        let mut inspector = TracingInspector::new(config);
+        assert_eq!(recorded_steps(code.clone(), &mut inspector), 2);
+        let ops = inspector.traces.arena[0].trace.steps.iter().map(|step| step.op.u8());
+        assert_eq!(ops.collect::<Vec<_>>(), vec![opcode::SSTORE, opcode::SLOAD]);
+
+        let config = TracingInspectorConfig::default_geth().exclude_opcodes(&[opcode::ADD]);
+        let mut inspector = TracingInspector::new(config);
+        assert_eq!(recorded_steps(code, &mut inspector), 7);
+    }
+}
@@ -1,7 +1,7 @@
 //! Types for representing call trace items.
 
 use crate::tracing::utils::convert_memory;
-use reth_primitives::{abi::decode_revert_reason, bytes::Bytes, Address, H256, U256};
+use reth_primitives::{bytes::Bytes, Address, H256, U256};
 use reth_rpc_types::trace::{
     geth::{CallFrame, CallLogFrame, GethDefaultTracingOptions, StructLog},
     parity::{
@@ -11,11 +11,52 @@ use reth_rpc_types::trace::{
     },
 };
 use revm::interpreter::{
-    opcode, CallContext, CallScheme, CreateScheme, InstructionResult, Memory, OpCode, Stack,
+    opcode, return_ok, CallContext, CallScheme, CreateScheme, InstructionResult, Memory, OpCode,
+    Stack,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::{btree_map::Entry, VecDeque};
 
+/// The overall outcome of a traced execution, derived from the status of the top-level call.
+///
+/// The status maps to the [ExecutionResult](revm::primitives::ExecutionResult) of the execution:
+///   - [ExecutionStatus::Success]: the call stopped, returned or selfdestructed
+///   - [ExecutionStatus::Revert]: the call reverted
+///   - [ExecutionStatus::OutOfGas]: the call halted with any of the out of gas results
+///   - [ExecutionStatus::Halt]: the call halted for any other reason, e.g. an invalid opcode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecutionStatus {
+    /// The execution succeeded.
+    Success,
+    /// The execution reverted.
+    Revert,
+    /// The execution ran out of gas.
+    OutOfGas,
+    /// The execution halted with the given result.
+    Halt(InstructionResult),
+}
+
+impl ExecutionStatus {
+    /// Returns the status for the given result of the top-level call.
+    pub fn from_instruction_result(result: InstructionResult) -> Self {
+        match result {
+            return_ok!() => ExecutionStatus::Success,
+            InstructionResult::Revert => ExecutionStatus::Revert,
+            InstructionResult::OutOfGas |
+            InstructionResult::MemoryOOG |
+            InstructionResult::MemoryLimitOOG |
+            InstructionResult::PrecompileOOG |
+            InstructionResult::InvalidOperandOOG => ExecutionStatus::OutOfGas,
+            result => ExecutionStatus::Halt(result),
+        }
+    }
+
+    /// Returns true if the execution succeeded.
+    pub fn is_success(&self) -> bool {
+        matches!(self, ExecutionStatus::Success)
+    }
+}
+
 /// A unified representation of a call
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
@@ -137,6 +178,8 @@ pub(crate) struct CallTrace {
     /// The return data of the call if this was not a contract creation, otherwise it is the
     /// runtime bytecode of the created contract
     pub(crate) output: Bytes,
+    /// The decoded revert reason, if the call reverted and decoding revert reasons is enabled
+    pub(crate) revert_reason: Option<String>,
     /// The return data of the last call, if any
     pub(crate) last_call_return_value: Option<Bytes>,
     /// The gas cost of the call
@@ -176,6 +219,7 @@ impl Default for CallTrace {
             data: Default::default(),
             maybe_precompile: None,
             output: Default::default(),
+            revert_reason: None,
             last_call_return_value: None,
             gas_used: Default::default(),
             gas_limit: Default::default(),
@@ -385,7 +429,7 @@ impl CallTraceNode {
 
         // we need to populate error and revert reason
         if !self.trace.success {
-            call_frame.revert_reason = decode_revert_reason(self.trace.output.clone());
+            call_frame.revert_reason = self.trace.revert_reason.clone();
             call_frame.error = self.trace.as_error();
         }
 
@@ -523,3 +567,32 @@ pub(crate) struct StorageChange {
     pub(crate) value: U256,
     pub(crate) had_value: Option<U256>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn execution_status_from_instruction_result() {
+        assert_eq!(
+            ExecutionStatus::from_instruction_result(InstructionResult::Stop),
+            ExecutionStatus::Success
+        );
+        assert_eq!(
+            ExecutionStatus::from_instruction_result(InstructionResult::Return),
+            ExecutionStatus::Success
+        );
+        assert_eq!(
+            ExecutionStatus::from_instruction_result(InstructionResult::Revert),
+            ExecutionStatus::Revert
+        );
+        assert_eq!(
+            ExecutionStatus::from_instruction_result(InstructionResult::MemoryOOG),
+            ExecutionStatus::OutOfGas
+        );
+        assert_eq!(
+            ExecutionStatus::from_instruction_result(InstructionResult::OpcodeNotFound),
+            ExecutionStatus::Halt(InstructionResult::OpcodeNotFound)
+        );
+    }
+}
@@ -1,8 +1,10 @@
 //! Util functions for revm related ops
 
 use reth_primitives::{
+    abi,
+    constants::SELECTOR_LEN,
     contract::{create2_address_from_code, create_address},
-    hex, Address,
+    hex, Address, U256,
 };
 use revm::{
     interpreter::CreateInputs,
@@ -21,6 +23,31 @@ pub(crate) fn convert_memory(data: &[u8]) -> Vec<String> {
     memory
 }
 
+/// The selector of the `Error(string)` revert reason.
+const ERROR_SELECTOR: [u8; SELECTOR_LEN] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// The selector of the `Panic(uint256)` revert reason.
+const PANIC_SELECTOR: [u8; SELECTOR_LEN] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Decodes the revert reason of a reverted call from its output.
+///
+/// Supports the standard `Error(string)` and `Panic(uint256)` selectors, returns `None` for any
+/// other output.
+pub(crate) fn decode_revert_reason(output: &[u8]) -> Option<String> {
+    if output.len() < SELECTOR_LEN {
+        return None
+    }
+    let (selector, data) = output.split_at(SELECTOR_LEN);
+    if selector == ERROR_SELECTOR {
+        return abi::decode_revert_reason(output)
+    }
+    if selector == PANIC_SELECTOR && data.len() == 32 {
+        let code = U256::try_from_be_slice(data)?;
+        return Some(format!("Panic({code:#x})"))
+    }
+    None
+}
+
 /// Get the gas used, accounting for refunds
 #[inline]
 #[allow(unused)]
@@ -39,3 +66,38 @@ pub(crate) fn get_create_address(call: &CreateInputs, nonce: u64) -> Address {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_error_revert_reason() {
+        // Error("revert")
+        let output = hex::decode(concat!(
+            "08c379a0",
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000006",
+            "7265766572740000000000000000000000000000000000000000000000000000"
+        ))
+        .unwrap();
+        assert_eq!(decode_revert_reason(&output), Some("revert".to_string()));
+    }
+
+    #[test]
+    fn decodes_panic_revert_reason() {
+        // Panic(0x11), arithmetic overflow
+        let output = hex::decode(concat!(
+            "4e487b71",
+            "0000000000000000000000000000000000000000000000000000000000000011"
+        ))
+        .unwrap();
+        assert_eq!(decode_revert_reason(&output), Some("Panic(0x11)".to_string()));
+    }
+
+    #[test]
+    fn ignores_unknown_revert_selector() {
+        assert_eq!(decode_revert_reason(&[]), None);
+        assert_eq!(decode_revert_reason(&[0xde, 0xad, 0xbe, 0xef, 0x00]), None);
+    }
+}
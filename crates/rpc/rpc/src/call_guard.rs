@@ -1,26 +1,601 @@
-use std::sync::Arc;
-use tokio::sync::{AcquireError, OwnedSemaphorePermit, Semaphore};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::{oneshot, AcquireError, OwnedSemaphorePermit, Semaphore};
 
 /// RPC Tracing call guard semaphore.
 ///
 /// This is used to restrict the number of concurrent RPC requests to tracing methods like
 /// `debug_traceTransaction` because they can consume a lot of memory and CPU.
 #[derive(Clone, Debug)]
-pub struct TracingCallGuard(Arc<Semaphore>);
+pub struct TracingCallGuard {
+    /// Limits the number of tracing calls in parallel.
+    semaphore: Arc<Semaphore>,
+    /// The waiters and held permits of [TracingCallGuard::acquire_fair].
+    fair_queue: Arc<Mutex<FairQueue>>,
+    /// Tracks the acquisitions of this guard.
+    metrics: Arc<GuardMetrics>,
+}
 
 impl TracingCallGuard {
     /// Create a new `TracingCallGuard` with the given maximum number of tracing calls in parallel.
     pub fn new(max_tracing_requests: u32) -> Self {
-        Self(Arc::new(Semaphore::new(max_tracing_requests as usize)))
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_tracing_requests as usize)),
+            fair_queue: Default::default(),
+            metrics: Arc::new(GuardMetrics::with_total_permits(max_tracing_requests as usize)),
+        }
+    }
+
+    /// Same as [TracingCallGuard::new], but invokes the given callback with the time spent waiting
+    /// every time a permit is acquired.
+    pub fn with_metrics(
+        max_tracing_requests: u32,
+        on_acquired: impl Fn(Duration) + Send + Sync + 'static,
+    ) -> Self {
+        let mut guard = Self::new(max_tracing_requests);
+        guard.metrics = Arc::new(GuardMetrics {
+            on_acquired: Some(Box::new(on_acquired)),
+            ..GuardMetrics::with_total_permits(max_tracing_requests as usize)
+        });
+        guard
+    }
+
+    /// Returns the total number of acquisitions, each acquisition of multiple permits is counted
+    /// once.
+    pub fn total_acquired(&self) -> u64 {
+        self.metrics.total_acquired.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total number of acquisitions that timed out, see
+    /// [TracingCallGuard::acquire_owned_timeout].
+    pub fn total_timed_out(&self) -> u64 {
+        self.metrics.total_timed_out.load(Ordering::Relaxed)
+    }
+
+    /// Adds `n` permits, increasing the number of tracing calls allowed in parallel.
+    pub fn add_permits(&self, n: usize) {
+        self.semaphore.add_permits(n);
+        self.metrics.total_permits.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Removes up to `n` currently available permits, decreasing the number of tracing calls
+    /// allowed in parallel.
+    ///
+    /// Permits that are currently held are not revoked, so this returns the number of permits that
+    /// were actually removed, which is less than `n` if fewer permits are available.
+    pub fn try_reduce_permits(&self, n: usize) -> usize {
+        let mut removed = 0;
+        while removed < n {
+            match self.semaphore.try_acquire() {
+                Ok(permit) => permit.forget(),
+                Err(_) => break,
+            }
+            removed += 1;
+        }
+        self.metrics.total_permits.fetch_sub(removed, Ordering::Relaxed);
+        removed
+    }
+
+    /// Returns the number of permits that are currently available.
+    pub fn available_permits(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// Returns a snapshot of the permits of this guard, e.g. for exporting them as metrics.
+    pub fn stats(&self) -> TracingCallStats {
+        let total_permits = self.metrics.total_permits.load(Ordering::Relaxed);
+        let available_permits = self.available_permits();
+        TracingCallStats {
+            total_permits,
+            available_permits,
+            in_flight: total_permits.saturating_sub(available_permits),
+        }
     }
 
     /// See also [Semaphore::acquire_owned]
     pub async fn acquire_owned(self) -> Result<OwnedSemaphorePermit, AcquireError> {
-        self.0.acquire_owned().await
+        let started = Instant::now();
+        let permit = self.semaphore.acquire_owned().await?;
+        self.metrics.record_acquired(started.elapsed());
+        Ok(permit)
+    }
+
+    /// Acquires a permit without waiting, returning `None` if all permits are currently held.
+    ///
+    /// This is meant for calls that should be rejected immediately as busy instead of being
+    /// queued. See also [Semaphore::try_acquire_owned]
+    pub fn try_acquire_owned(&self) -> Option<OwnedSemaphorePermit> {
+        let permit = self.semaphore.clone().try_acquire_owned().ok()?;
+        self.metrics.record_acquired(Duration::ZERO);
+        Some(permit)
+    }
+
+    /// Same as [TracingCallGuard::acquire_owned] but fails with [TracingCallError::Timeout] if no
+    /// permit becomes available within the given timeout.
+    pub async fn acquire_owned_timeout(
+        self,
+        timeout: Duration,
+    ) -> Result<OwnedSemaphorePermit, TracingCallError> {
+        let metrics = self.metrics.clone();
+        let permit =
+            tokio::time::timeout(timeout, self.acquire_owned()).await.map_err(|_| {
+                metrics.total_timed_out.fetch_add(1, Ordering::Relaxed);
+                TracingCallError::Timeout
+            })??;
+        Ok(permit)
     }
 
     /// See also [Semaphore::acquire_many_owned]
     pub async fn acquire_many_owned(self, n: u32) -> Result<OwnedSemaphorePermit, AcquireError> {
-        self.0.acquire_many_owned(n).await
+        let started = Instant::now();
+        let permit = self.semaphore.acquire_many_owned(n).await?;
+        self.metrics.record_acquired(started.elapsed());
+        Ok(permit)
+    }
+
+    /// Acquires as many permits as the given [TracingCallWeight] requires.
+    ///
+    /// This waits indefinitely if the guard has fewer permits in total than the weight requires.
+    pub async fn acquire_for(
+        self,
+        weight: TracingCallWeight,
+    ) -> Result<OwnedSemaphorePermit, AcquireError> {
+        self.acquire_many_owned(weight.permits()).await
+    }
+
+    /// Acquires a permit on behalf of the given client, sharing the permits fairly between all
+    /// clients that call this.
+    ///
+    /// Whenever a permit frees up, it is granted to the waiting client with the fewest permits
+    /// currently held via this function, ties are broken in arrival order. This prevents a single
+    /// client from monopolizing the tracing permits, but it does not preempt permits that are
+    /// already held, and permits acquired via [TracingCallGuard::acquire_owned] are not accounted
+    /// to any client.
+    ///
+    /// Clients are only tracked while they hold or wait for a permit, so the memory used is
+    /// bounded by the number of in-flight and waiting calls.
+    pub async fn acquire_fair(self, client_id: u64) -> Result<FairTracingPermit, AcquireError> {
+        let started = Instant::now();
+        let (tx, mut rx) = oneshot::channel();
+        let waiter = self.fair_queue.lock().expect("not poisoned").push_waiter(client_id, tx);
+        // unregisters the waiter if this future is dropped before a permit was granted
+        let _guard = WaiterGuard { queue: self.fair_queue.clone(), waiter };
+
+        loop {
+            tokio::select! {
+                permit = self.semaphore.clone().acquire_owned() => {
+                    let mut permit = permit?;
+                    let mut queue = self.fair_queue.lock().expect("not poisoned");
+                    // hand the permit to the most deserving waiter, which may be this one
+                    while let Some((next, next_tx)) = queue.pop_next_waiter() {
+                        if next == waiter {
+                            drop(queue);
+                            self.metrics.record_acquired(started.elapsed());
+                            return Ok(FairTracingPermit::new(permit, self.fair_queue, client_id))
+                        }
+                        match next_tx.send(permit) {
+                            Ok(()) => break,
+                            // the waiter was dropped in the meantime, try the next one
+                            Err(unsent) => permit = unsent,
+                        }
+                    }
+                }
+                permit = &mut rx => {
+                    let permit = permit.expect("sender is only dropped after sending");
+                    self.metrics.record_acquired(started.elapsed());
+                    return Ok(FairTracingPermit::new(permit, self.fair_queue, client_id))
+                }
+            }
+        }
+    }
+}
+
+/// The class of a tracing method, each class is limited by its own [TracingCallGuard] in
+/// [TracingCallGuards].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TracingMethodClass {
+    /// `debug_trace*` methods that trace existing transactions or blocks.
+    Debug,
+    /// `trace_*` methods that trace existing transactions or blocks.
+    Trace,
+    /// Methods that trace calls which are not part of the chain, e.g. `trace_call`.
+    Call,
+}
+
+impl TracingMethodClass {
+    /// Returns the class of the given RPC method.
+    pub fn from_method(method: &str) -> Self {
+        match method {
+            "trace_call" | "trace_callMany" | "trace_rawTransaction" | "debug_traceCall" => {
+                TracingMethodClass::Call
+            }
+            method if method.starts_with("debug_") => TracingMethodClass::Debug,
+            _ => TracingMethodClass::Trace,
+        }
+    }
+}
+
+/// Separate [TracingCallGuard]s per [TracingMethodClass], with independent limits.
+///
+/// This prevents a flood of cheap tracing calls from starving expensive ones and vice versa.
+#[derive(Clone, Debug)]
+pub struct TracingCallGuards {
+    debug: TracingCallGuard,
+    trace: TracingCallGuard,
+    call: TracingCallGuard,
+}
+
+impl TracingCallGuards {
+    /// Creates the guards with the given maximum number of tracing calls in parallel per class.
+    pub fn new(max_debug_requests: u32, max_trace_requests: u32, max_call_requests: u32) -> Self {
+        Self {
+            debug: TracingCallGuard::new(max_debug_requests),
+            trace: TracingCallGuard::new(max_trace_requests),
+            call: TracingCallGuard::new(max_call_requests),
+        }
+    }
+
+    /// Returns the guard of the given class.
+    pub fn guard(&self, class: TracingMethodClass) -> &TracingCallGuard {
+        match class {
+            TracingMethodClass::Debug => &self.debug,
+            TracingMethodClass::Trace => &self.trace,
+            TracingMethodClass::Call => &self.call,
+        }
+    }
+
+    /// Returns the guard for the given RPC method, see [TracingMethodClass::from_method].
+    pub fn guard_for(&self, method: &str) -> &TracingCallGuard {
+        self.guard(TracingMethodClass::from_method(method))
+    }
+}
+
+/// The cost of a tracing call in permits of the [TracingCallGuard], based on the kind of call.
+///
+/// Heavier calls acquire more permits, so they are throttled more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TracingCallWeight {
+    /// Tracing a single transaction or call, e.g. `debug_traceTransaction`.
+    Transaction,
+    /// Tracing multiple calls, e.g. `trace_callMany`.
+    CallMany,
+    /// Tracing all transactions of a block, e.g. `debug_traceBlock`.
+    Block,
+}
+
+impl TracingCallWeight {
+    /// Returns the number of permits a call of this kind acquires.
+    pub const fn permits(&self) -> u32 {
+        match self {
+            TracingCallWeight::Transaction => 1,
+            TracingCallWeight::CallMany => 2,
+            TracingCallWeight::Block => 4,
+        }
+    }
+}
+
+/// A snapshot of the permits of a [TracingCallGuard], see [TracingCallGuard::stats].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TracingCallStats {
+    /// The number of permits the guard was configured with.
+    pub total_permits: usize,
+    /// The number of permits that are currently available.
+    pub available_permits: usize,
+    /// The number of permits that are currently held, weighted calls hold more than one.
+    pub in_flight: usize,
+}
+
+/// Errors that can occur when acquiring a permit of the [TracingCallGuard].
+#[derive(Debug, thiserror::Error)]
+pub enum TracingCallError {
+    /// No permit became available in time.
+    #[error("timed out waiting for a tracing permit")]
+    Timeout,
+    /// The semaphore was closed.
+    #[error(transparent)]
+    Closed(#[from] AcquireError),
+}
+
+/// A permit acquired via [TracingCallGuard::acquire_fair].
+///
+/// The permit is released and the client's share is updated when this is dropped.
+#[derive(Debug)]
+pub struct FairTracingPermit {
+    _permit: OwnedSemaphorePermit,
+    queue: Arc<Mutex<FairQueue>>,
+    client_id: u64,
+}
+
+impl FairTracingPermit {
+    /// Accounts the permit to the given client.
+    fn new(permit: OwnedSemaphorePermit, queue: Arc<Mutex<FairQueue>>, client_id: u64) -> Self {
+        queue.lock().expect("not poisoned").start(client_id);
+        Self { _permit: permit, queue, client_id }
+    }
+}
+
+impl Drop for FairTracingPermit {
+    fn drop(&mut self) {
+        self.queue.lock().expect("not poisoned").finish(self.client_id);
+    }
+}
+
+/// Tracks the acquisitions of a [TracingCallGuard].
+#[derive(Default)]
+struct GuardMetrics {
+    /// Invoked with the time spent waiting for every acquisition.
+    on_acquired: Option<Box<dyn Fn(Duration) + Send + Sync>>,
+    /// The total number of acquisitions.
+    total_acquired: AtomicU64,
+    /// The total number of acquisitions that timed out.
+    total_timed_out: AtomicU64,
+    /// The number of permits the guard was configured with, including resizes.
+    total_permits: AtomicUsize,
+}
+
+impl GuardMetrics {
+    fn with_total_permits(total_permits: usize) -> Self {
+        Self { total_permits: AtomicUsize::new(total_permits), ..Default::default() }
+    }
+
+    fn record_acquired(&self, waited: Duration) {
+        self.total_acquired.fetch_add(1, Ordering::Relaxed);
+        if let Some(on_acquired) = &self.on_acquired {
+            on_acquired(waited);
+        }
+    }
+}
+
+impl fmt::Debug for GuardMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GuardMetrics")
+            .field("total_acquired", &self.total_acquired)
+            .field("total_timed_out", &self.total_timed_out)
+            .field("total_permits", &self.total_permits)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Removes a waiter from the [FairQueue] when its future is dropped.
+struct WaiterGuard {
+    queue: Arc<Mutex<FairQueue>>,
+    waiter: WaiterId,
+}
+
+impl Drop for WaiterGuard {
+    fn drop(&mut self) {
+        self.queue.lock().expect("not poisoned").remove_waiter(self.waiter);
+    }
+}
+
+/// Identifies a waiter of [TracingCallGuard::acquire_fair], in arrival order.
+type WaiterId = u64;
+
+/// Tracks the waiting clients and the permits held by each client.
+#[derive(Debug, Default)]
+struct FairQueue {
+    /// The id of the next waiter.
+    next_waiter: WaiterId,
+    /// All waiters, in arrival order.
+    waiters: VecDeque<(WaiterId, u64, oneshot::Sender<OwnedSemaphorePermit>)>,
+    /// Number of permits held per client, clients without permits are removed.
+    in_flight: HashMap<u64, usize>,
+}
+
+impl FairQueue {
+    fn push_waiter(
+        &mut self,
+        client_id: u64,
+        tx: oneshot::Sender<OwnedSemaphorePermit>,
+    ) -> WaiterId {
+        let waiter = self.next_waiter;
+        self.next_waiter += 1;
+        self.waiters.push_back((waiter, client_id, tx));
+        waiter
+    }
+
+    fn remove_waiter(&mut self, waiter: WaiterId) {
+        self.waiters.retain(|(id, _, _)| *id != waiter);
+    }
+
+    /// Removes and returns the first waiter of the client with the fewest permits.
+    fn pop_next_waiter(&mut self) -> Option<(WaiterId, oneshot::Sender<OwnedSemaphorePermit>)> {
+        let idx = self
+            .waiters
+            .iter()
+            .enumerate()
+            .min_by_key(|(idx, (_, client_id, _))| {
+                (self.in_flight.get(client_id).copied().unwrap_or_default(), *idx)
+            })
+            .map(|(idx, _)| idx)?;
+        let (waiter, _, tx) = self.waiters.remove(idx)?;
+        Some((waiter, tx))
+    }
+
+    fn start(&mut self, client_id: u64) {
+        *self.in_flight.entry(client_id).or_default() += 1;
+    }
+
+    fn finish(&mut self, client_id: u64) {
+        if let Some(count) = self.in_flight.get_mut(&client_id) {
+            *count -= 1;
+            if *count == 0 {
+                self.in_flight.remove(&client_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn acquire_fair_prefers_underrepresented_clients() {
+        let guard = TracingCallGuard::new(2);
+        let _busy = guard.clone().acquire_fair(1).await.unwrap();
+        let other = guard.clone().acquire_owned().await.unwrap();
+
+        // client 1 queues up before client 2, but already holds a permit
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        for client_id in [1, 1, 2] {
+            let guard = guard.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let _permit = guard.acquire_fair(client_id).await.unwrap();
+                tx.send(client_id).unwrap();
+                futures::future::pending::<()>().await;
+            });
+            tokio::task::yield_now().await;
+        }
+
+        drop(other);
+        assert_eq!(rx.recv().await, Some(2));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn stats_snapshot() {
+        let guard = TracingCallGuard::new(4);
+        let _first = guard.clone().acquire_owned().await.unwrap();
+        let _second = guard.clone().acquire_owned().await.unwrap();
+        assert_eq!(
+            guard.stats(),
+            TracingCallStats { total_permits: 4, available_permits: 2, in_flight: 2 }
+        );
+
+        guard.add_permits(2);
+        assert_eq!(guard.try_reduce_permits(1), 1);
+        assert_eq!(
+            guard.stats(),
+            TracingCallStats { total_permits: 5, available_permits: 3, in_flight: 2 }
+        );
+    }
+
+    #[test]
+    fn method_classes() {
+        assert_eq!(
+            TracingMethodClass::from_method("debug_traceBlockByNumber"),
+            TracingMethodClass::Debug
+        );
+        assert_eq!(TracingMethodClass::from_method("debug_traceCall"), TracingMethodClass::Call);
+        assert_eq!(TracingMethodClass::from_method("trace_block"), TracingMethodClass::Trace);
+        assert_eq!(TracingMethodClass::from_method("trace_callMany"), TracingMethodClass::Call);
+    }
+
+    #[tokio::test]
+    async fn independent_guard_pools() {
+        let guards = TracingCallGuards::new(1, 1, 1);
+        let _debug = guards.guard_for("debug_traceTransaction").clone().acquire_owned().await;
+
+        let timeout = Duration::from_millis(10);
+        let res = guards.guard_for("debug_traceBlockByHash").clone().acquire_owned_timeout(timeout);
+        assert!(matches!(res.await, Err(TracingCallError::Timeout)));
+
+        let res = guards.guard_for("trace_block").clone().acquire_owned_timeout(timeout).await;
+        assert!(res.is_ok());
+    }
+
+    #[tokio::test]
+    async fn records_wait_durations() {
+        let waits = Arc::new(Mutex::new(Vec::new()));
+        let recorded = waits.clone();
+        let guard = TracingCallGuard::with_metrics(1, move |waited| {
+            recorded.lock().unwrap().push(waited);
+        });
+
+        let permit = guard.clone().acquire_owned().await.unwrap();
+        let contended = tokio::spawn(guard.clone().acquire_owned());
+        let res = guard.clone().acquire_owned_timeout(Duration::from_millis(20)).await;
+        assert!(matches!(res, Err(TracingCallError::Timeout)));
+        drop(permit);
+        drop(contended.await.unwrap().unwrap());
+
+        assert_eq!(guard.total_acquired(), 2);
+        assert_eq!(guard.total_timed_out(), 1);
+        let waits = waits.lock().unwrap();
+        assert_eq!(waits.len(), 2);
+        assert!(waits[1] > Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn acquire_weighted() {
+        let guard = TracingCallGuard::new(6);
+        let block = guard.clone().acquire_for(TracingCallWeight::Block).await.unwrap();
+        assert_eq!(guard.available_permits(), 2);
+        drop(block);
+
+        let tx = guard.clone().acquire_for(TracingCallWeight::Transaction).await.unwrap();
+        assert_eq!(guard.available_permits(), 5);
+        let _call_many = guard.clone().acquire_for(TracingCallWeight::CallMany).await.unwrap();
+        assert_eq!(guard.available_permits(), 3);
+        drop(tx);
+        assert!(
+            TracingCallWeight::Block.permits() > TracingCallWeight::CallMany.permits() &&
+                TracingCallWeight::CallMany.permits() > TracingCallWeight::Transaction.permits()
+        );
+    }
+
+    #[tokio::test]
+    async fn acquire_times_out() {
+        let guard = TracingCallGuard::new(0);
+        let res = guard.clone().acquire_owned_timeout(Duration::from_millis(10)).await;
+        assert!(matches!(res, Err(TracingCallError::Timeout)));
+
+        guard.add_permits(1);
+        assert!(guard.acquire_owned_timeout(Duration::from_millis(10)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn try_acquire_rejects_at_capacity() {
+        let guard = TracingCallGuard::new(2);
+        let first = guard.try_acquire_owned().unwrap();
+        let _second = guard.clone().acquire_owned().await.unwrap();
+        assert!(guard.try_acquire_owned().is_none());
+
+        drop(first);
+        assert!(guard.try_acquire_owned().is_some());
+        assert_eq!(guard.total_acquired(), 3);
+    }
+
+    #[tokio::test]
+    async fn resize_permits() {
+        let guard = TracingCallGuard::new(1);
+        let first = guard.clone().acquire_owned().await.unwrap();
+        assert_eq!(guard.available_permits(), 0);
+
+        guard.add_permits(2);
+        assert_eq!(guard.available_permits(), 2);
+        let second = guard.clone().acquire_owned().await.unwrap();
+
+        // only the available permit can be removed
+        assert_eq!(guard.try_reduce_permits(3), 1);
+        assert_eq!(guard.available_permits(), 0);
+        assert!(guard.semaphore.clone().try_acquire_owned().is_err());
+
+        drop(first);
+        drop(second);
+        assert_eq!(guard.available_permits(), 2);
+        assert_eq!(guard.try_reduce_permits(1), 1);
+        assert_eq!(guard.available_permits(), 1);
+    }
+
+    #[tokio::test]
+    async fn fair_permits_are_released() {
+        let guard = TracingCallGuard::new(1);
+        let permit = guard.clone().acquire_fair(7).await.unwrap();
+        assert_eq!(guard.fair_queue.lock().unwrap().in_flight.get(&7), Some(&1));
+
+        drop(permit);
+        assert!(guard.fair_queue.lock().unwrap().in_flight.is_empty());
+        let _permit = guard.clone().acquire_fair(8).await.unwrap();
     }
 }
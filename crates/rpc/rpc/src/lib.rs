@@ -37,7 +37,10 @@ mod txpool;
 mod web3;
 
 pub use admin::AdminApi;
-pub use call_guard::TracingCallGuard;
+pub use call_guard::{
+    FairTracingPermit, TracingCallError, TracingCallGuard, TracingCallGuards, TracingCallStats,
+    TracingCallWeight, TracingMethodClass,
+};
 pub use debug::DebugApi;
 pub use engine::{EngineApi, EngineEthApi};
 pub use eth::{EthApi, EthApiSpec, EthFilter, EthPubSub, EthSubscriptionIdProvider};
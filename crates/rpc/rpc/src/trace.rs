@@ -124,7 +124,7 @@ where
         overrides: EvmOverrides,
     ) -> EthResult<TraceResults> {
         let at = block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
-        let config = tracing_config(&trace_types);
+        let config = TracingInspectorConfig::from_parity_config(&trace_types);
         let mut inspector = TracingInspector::new(config);
 
         let (res, _) =
@@ -152,7 +152,7 @@ where
         let tx = tx_env_with_recovered(&tx);
         let env = Env { cfg, block, tx };
 
-        let config = tracing_config(&trace_types);
+        let config = TracingInspectorConfig::from_parity_config(&trace_types);
 
         self.on_blocking_task(|this| async move {
             this.inner.eth_api.trace_at(env, config, at, |inspector, res| {
@@ -190,7 +190,7 @@ where
                         &mut db,
                         Default::default(),
                     )?;
-                    let config = tracing_config(&trace_types);
+                    let config = TracingInspectorConfig::from_parity_config(&trace_types);
                     let mut inspector = TracingInspector::new(config);
                     let (res, _) = inspect(&mut db, env, &mut inspector)?;
                     let trace_res = inspector
@@ -211,7 +211,7 @@ where
         hash: H256,
         trace_types: HashSet<TraceType>,
     ) -> EthResult<TraceResults> {
-        let config = tracing_config(&trace_types);
+        let config = TracingInspectorConfig::from_parity_config(&trace_types);
         self.on_blocking_task(|this| async move {
             this.inner
                 .eth_api
@@ -366,7 +366,7 @@ where
     ) -> EthResult<Option<Vec<TraceResultsWithTransactionHash>>> {
         self.trace_block_with(
             block_id,
-            tracing_config(&trace_types),
+            TracingInspectorConfig::from_parity_config(&trace_types),
             move |tx_info, inspector, res| {
                 let full_trace =
                     inspector.into_parity_builder().into_trace_results(res, &trace_types);
@@ -510,10 +510,3 @@ struct TraceApiInner<Provider, Eth> {
     // restrict the number of concurrent calls to `trace_*`
     tracing_call_guard: TracingCallGuard,
 }
-
-/// Returns the [TracingInspectorConfig] depending on the enabled [TraceType]s
-fn tracing_config(trace_types: &HashSet<TraceType>) -> TracingInspectorConfig {
-    TracingInspectorConfig::default_parity()
-        .set_state_diffs(trace_types.contains(&TraceType::StateDiff))
-        .set_steps(trace_types.contains(&TraceType::VmTrace))
-}